@@ -5,30 +5,59 @@ day=$1
 export day
 
 if [[ ! -f "./src/day${day}.rs" ]]; then
-    envsubst <<EOF > ./src/day${day}.rs
+    envsubst <<'EOF' > ./src/day${day}.rs
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::solution::Solution;
+
 #[aoc_generator(day$day)]
-pub fn parse(input: &str) -> Vec<usize> {
-    input.lines().map(|l| l.parse::<usize>().unwrap()).collect()
+pub fn parse(input: &str) -> Result<Vec<usize>, ParseError> {
+    let src = Source::new($day, input);
+    input.lines().map(|l| src.parse(l, "a number")).collect()
 }
+
 #[aoc(day$day, part1)]
-pub fn part1(input: &[usize]) -> usize {
-    0
+pub fn part1(input: &[usize]) -> Answer {
+    0.into()
 }
+
 #[aoc(day$day, part2)]
-pub fn part2(input: &[usize]) -> usize {
-    0
+pub fn part2(input: &[usize]) -> Answer {
+    0.into()
+}
+
+pub struct Day$day;
+
+impl Solution for Day$day {
+    const DAY: u8 = $day;
+
+    type Parsed = Vec<usize>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
     const INPUT: &str = "";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 0)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(0))
     }
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 0)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(0))
     }
 }
 EOF
@@ -37,3 +66,8 @@ fi
 if ! grep -q day${day} src/lib.rs; then
     sed -i "/aoc_lib.*/i pub mod day${day};" ./src/lib.rs
 fi
+
+if ! grep -q "day${day}::" src/registry.rs; then
+    sed -i "/^pub static DAYS/,/^];/{/^];/i \    Day::of::<crate::day${day}::Day${day}>(),
+}" ./src/registry.rs
+fi
@@ -0,0 +1,58 @@
+import pytest
+
+import aoc2024
+
+GARDEN = """RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+"""
+
+QUINE = """Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+"""
+
+
+def test_regions():
+    garden = aoc2024.Garden(GARDEN)
+    regions = garden.regions()
+    assert len(regions) == 11
+    first = regions[0]
+    assert (first.plant, first.area, first.perimeter, first.sides) == ("R", 12, 18, 10)
+    assert first.plots[:2] == [(0, 0), (1, 0)]
+    assert repr(first) == "Region(plant='R', area=12, perimeter=18, sides=10)"
+    assert sum(r.area * r.perimeter for r in regions) == garden.part1() == 1930
+    assert sum(r.area * r.sides for r in regions) == garden.part2() == 1206
+
+
+def test_garden_parse_error():
+    with pytest.raises(aoc2024.ParseError):
+        aoc2024.Garden("AB\nc")
+
+
+def test_computer():
+    computer = aoc2024.Computer(QUINE)
+    assert computer.registers == (2024, 0, 0)
+    assert computer.program == [0, 3, 5, 4, 3, 0]
+    assert computer.run() == [5, 7, 3, 0]
+    a = computer.part2()
+    assert a == 117440
+    assert computer.run(a) == computer.program
+
+
+def test_computer_registers():
+    computer = aoc2024.Computer(
+        "Register A: 0\nRegister B: 13\nRegister C: 3\n\nProgram: 5,5,5,6\n"
+    )
+    assert computer.registers == (0, 13, 3)
+    assert computer.run() == [5, 3]
+    assert computer.part1() == "5,3"
@@ -0,0 +1,60 @@
+import pytest
+
+import aoc2024
+
+DAY1 = """3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+"""
+
+PROGRAM = """Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0
+"""
+
+
+def test_days():
+    assert aoc2024.days() == list(range(1, 26))
+    assert aoc2024.params(11) == ["blinks1", "blinks2"]
+    assert aoc2024.params(1) == []
+
+
+def test_solve():
+    assert aoc2024.solve(1, 1, DAY1) == 11
+    assert aoc2024.solve(1, 2, DAY1) == 31
+    assert aoc2024.solve(17, 1, PROGRAM) == "4,6,3,5,6,3,5,2,1,0"
+
+
+def test_line_endings():
+    assert aoc2024.solve(1, 1, DAY1.replace("\n", "\r\n")) == 11
+
+
+def test_params():
+    assert aoc2024.solve(11, 1, "125 17", {"blinks1": 6}) == 22
+    assert aoc2024.solve(11, 1, "125 17", params={"blinks1": "25"}) == 55312
+
+
+def test_parse_error():
+    with pytest.raises(aoc2024.ParseError) as info:
+        aoc2024.solve(1, 1, "3   4\n4 x")
+    assert (info.value.line, info.value.column) == (2, 3)
+    assert info.value.expected
+    assert isinstance(info.value, ValueError)
+
+
+def test_param_error():
+    with pytest.raises(aoc2024.ParamError):
+        aoc2024.solve(11, 1, "125 17", {"blinks": 6})
+    with pytest.raises(aoc2024.ParamError):
+        aoc2024.solve(11, 1, "125 17", {"blinks1": "many"})
+
+
+@pytest.mark.parametrize("day, part", [(0, 1), (26, 1), (1, 3)])
+def test_unknown(day, part):
+    with pytest.raises(ValueError):
+        aoc2024.solve(day, part, DAY1)
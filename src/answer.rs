@@ -0,0 +1,101 @@
+use std::fmt;
+
+/// Result of a puzzle part.
+///
+/// The `From` conversions normalise numbers so that equal values compare
+/// equal: non-negative integers become `Int`, and `Big` is only used past
+/// `u64::MAX`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(u64),
+    Signed(i64),
+    Big(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Big(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Self {
+        match u64::try_from(n) {
+            Ok(n) => Answer::Int(n),
+            Err(_) => Answer::Big(n),
+        }
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(n: i128) -> Self {
+        match (u128::try_from(n), i64::try_from(n)) {
+            (Ok(n), _) => n.into(),
+            (_, Ok(n)) => Answer::Signed(n),
+            _ => panic!("{n} does not fit in an answer"),
+        }
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty => $wide:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    (n as $wide).into()
+                }
+            }
+        )*
+    };
+}
+
+from_int!(u32 => u128, u64 => u128, usize => u128, i32 => i128, i64 => i128, isize => i128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_owned())
+    }
+}
+
+#[cfg(test)]
+impl Answer {
+    /// Value of an `Int` answer, for ordering answers in tests.
+    pub(crate) fn int(&self) -> u64 {
+        match self {
+            Answer::Int(n) => *n,
+            _ => panic!("{self:?} is not a non-negative integer"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalised() {
+        assert_eq!(Answer::from(42isize), Answer::from(42u32));
+        assert_eq!(Answer::from(-3i64), Answer::Signed(-3));
+        assert_eq!(Answer::from(u128::MAX), Answer::Big(u128::MAX));
+        assert_eq!(Answer::from(7u128), Answer::Int(7));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::from(-12isize).to_string(), "-12");
+        assert_eq!(Answer::from("6,1").to_string(), "6,1");
+        assert_eq!(Answer::Big(1 << 70).to_string(), "1180591620717411303424");
+    }
+}
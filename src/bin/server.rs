@@ -0,0 +1,27 @@
+use std::process::ExitCode;
+
+use aoc2024::server;
+use clap::Parser;
+use tiny_http::Server;
+
+#[derive(Parser)]
+#[command(about = "Serve the Advent of Code 2024 solvers over HTTP")]
+struct Cli {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:2024")]
+    addr: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let server = match Server::http(&cli.addr) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("{}: {e}", cli.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("listening on http://{}", server.server_addr());
+    server::serve(&server);
+    ExitCode::SUCCESS
+}
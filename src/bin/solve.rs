@@ -0,0 +1,263 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use aoc2024::error::SolveError;
+use aoc2024::registry::{self, Day, Overrides};
+use aoc2024::timing::{Report, Table};
+use aoc2024::viz::{self, Recorder};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{Value, json};
+
+#[derive(Parser)]
+#[command(about = "Run the Advent of Code 2024 solvers outside of cargo-aoc")]
+struct Cli {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text, global = true)]
+    format: Format,
+    /// Also report wall time and allocations of each phase
+    #[arg(long, global = true)]
+    time: bool,
+    /// Log solver events up to LEVEL on stderr: error, warn, info, debug or trace
+    #[cfg(feature = "tracing")]
+    #[arg(long, value_name = "LEVEL", global = true)]
+    trace: Option<tracing::Level>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve one day
+    Run {
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Part to run, both when omitted
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+        /// Input file, `-` for stdin [default: input/2024/dayN.txt]
+        #[arg(long)]
+        input: Option<PathBuf>,
+        /// Override a puzzle parameter, e.g. `--param blinks1=6`
+        #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_override)]
+        params: Vec<(String, String)>,
+        /// Write the frames of each part into DIR/partN, for animated days
+        #[arg(long, value_name = "DIR")]
+        viz: Option<PathBuf>,
+        /// Format of the frames: text, ansi, ppm or pgm
+        #[arg(long, default_value = "text", requires = "viz")]
+        viz_format: viz::Format,
+        /// Keep one frame out of N, the last one always being kept
+        #[arg(long, value_name = "N", default_value_t = 1, requires = "viz")]
+        viz_every: usize,
+    },
+    /// Solve every day against input/2024/dayN.txt
+    All {
+        #[arg(long, default_value = "input/2024")]
+        input_dir: PathBuf,
+    },
+}
+
+enum Outcome {
+    Solved(Report),
+    Invalid(SolveError),
+    Unreadable(String),
+}
+
+fn read_input(path: &Path) -> Result<String, String> {
+    let mut input = String::new();
+    if path == Path::new("-") {
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("stdin: {e}"))?;
+    } else {
+        input = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    Ok(input)
+}
+
+fn parse_override(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        .ok_or_else(|| format!("expected KEY=VALUE, found {s:?}"))
+}
+
+fn solve(day: &Day, path: &Path, parts: &[u8], overrides: &Overrides) -> Outcome {
+    match read_input(path) {
+        Ok(input) => solve_input(day, &input, parts, overrides),
+        Err(e) => Outcome::Unreadable(e),
+    }
+}
+
+fn solve_input(day: &Day, input: &str, parts: &[u8], overrides: &Overrides) -> Outcome {
+    match (day.timed)(input, parts, overrides) {
+        Ok(report) => Outcome::Solved(report),
+        Err(e) => Outcome::Invalid(e),
+    }
+}
+
+fn animate(
+    day: u8,
+    input: &str,
+    parts: &[u8],
+    overrides: &Overrides,
+    dir: &Path,
+    format: viz::Format,
+    every: usize,
+) -> Result<(), String> {
+    let animation = viz::get(day).ok_or_else(|| format!("day {day} has no animation"))?;
+    for &part in parts {
+        let dir = dir.join(format!("part{part}"));
+        let recorder = Recorder::new(&dir, format).every(every);
+        let frames =
+            (animation.record)(input, part, overrides, &recorder).map_err(|e| e.to_string())?;
+        eprintln!("day{day} part{part}: {frames} frames in {}", dir.display());
+    }
+    Ok(())
+}
+
+fn to_json(day: u8, outcome: &Outcome) -> Vec<Value> {
+    match outcome {
+        Outcome::Solved(report) => report
+            .parts
+            .iter()
+            .map(|(part, answer, _)| json!({ "day": day, "part": part, "answer": answer.to_string() }))
+            .collect(),
+        Outcome::Invalid(SolveError::Parse(e)) => vec![json!({
+            "day": day,
+            "error": {
+                "line": e.line,
+                "column": e.column,
+                "expected": e.expected,
+                "found": e.found,
+                "message": e.to_string(),
+            },
+        })],
+        Outcome::Invalid(e @ SolveError::Param(_)) => {
+            vec![json!({ "day": day, "error": { "message": e.to_string() } })]
+        }
+        Outcome::Unreadable(e) => vec![json!({ "day": day, "error": { "message": e } })],
+    }
+}
+
+fn print_text(day: u8, outcome: &Outcome) {
+    match outcome {
+        Outcome::Solved(report) => report
+            .parts
+            .iter()
+            .for_each(|(part, answer, _)| println!("day{day} part{part}: {answer}")),
+        Outcome::Invalid(e) => eprintln!("{e}"),
+        Outcome::Unreadable(e) => eprintln!("day {day}: {e}"),
+    }
+}
+
+fn timings_json(report: &Report) -> Vec<Value> {
+    report
+        .phases()
+        .map(|(phase, m)| {
+            let mut v = json!({
+                "day": report.day,
+                "phase": phase,
+                "nanos": m.elapsed.as_nanos() as u64,
+            });
+            if let Some(a) = m.allocs {
+                v["allocs"] = a.count.into();
+                v["bytes"] = a.bytes.into();
+            }
+            v
+        })
+        .collect()
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    #[cfg(feature = "tracing")]
+    if let Some(level) = cli.trace {
+        use std::io::IsTerminal;
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_writer(std::io::stderr)
+            .with_ansi(std::io::stderr().is_terminal())
+            .init();
+    }
+    let outcomes = match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            params,
+            viz,
+            viz_format,
+            viz_every,
+        } => {
+            let Some(entry) = registry::get(day) else {
+                eprintln!("day {day} is not solved yet");
+                return ExitCode::FAILURE;
+            };
+            let path = input.unwrap_or_else(|| format!("input/2024/day{day}.txt").into());
+            let parts = part.map_or(vec![1, 2], |p| vec![p]);
+            let overrides: Vec<(&str, &str)> = params
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            let input = read_input(&path);
+            let outcome = match &input {
+                Ok(input) => solve_input(entry, input, &parts, &overrides),
+                Err(e) => Outcome::Unreadable(e.clone()),
+            };
+            if let (Some(dir), Ok(input), Outcome::Solved(_)) = (viz, &input, &outcome)
+                && let Err(e) = animate(day, input, &parts, &overrides, &dir, viz_format, viz_every)
+            {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+            vec![(day, outcome)]
+        }
+        Command::All { input_dir } => registry::DAYS
+            .iter()
+            .map(|d| {
+                let path = input_dir.join(format!("day{}.txt", d.day));
+                (d.day, solve(d, &path, &[1, 2], &[]))
+            })
+            .collect(),
+    };
+
+    let reports: Vec<Report> = outcomes
+        .iter()
+        .filter_map(|(_, o)| match o {
+            Outcome::Solved(report) if cli.time => Some(report.clone()),
+            _ => None,
+        })
+        .collect();
+    match cli.format {
+        Format::Text => {
+            outcomes.iter().for_each(|(d, o)| print_text(*d, o));
+            if cli.time {
+                print!("\n{}", Table(&reports));
+            }
+        }
+        Format::Json => {
+            let results: Vec<Value> = outcomes
+                .iter()
+                .flat_map(|(d, o)| to_json(*d, o))
+                .chain(reports.iter().flat_map(timings_json))
+                .collect();
+            println!("{}", Value::Array(results));
+        }
+    }
+
+    if outcomes
+        .iter()
+        .all(|(_, o)| matches!(o, Outcome::Solved(_)))
+    {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
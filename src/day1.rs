@@ -1,26 +1,32 @@
 use itertools::Itertools;
+use nom::character::complete::space1;
+use nom::sequence::separated_pair;
 use rustc_hash::FxHashMap as HashMap;
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{lines, number};
+use crate::solution::Solution;
+
 #[aoc_generator(day1)]
-pub fn parse(input: &str) -> (Vec<usize>, Vec<usize>) {
-    input
-        .lines()
-        .map(|l| l.split_once("   ").unwrap())
-        .map(|e| (e.0.parse::<usize>().unwrap(), e.1.parse::<usize>().unwrap()))
-        .unzip()
+pub fn parse(input: &str) -> Result<(Vec<usize>, Vec<usize>), ParseError> {
+    let id = || number::<usize>("a location id");
+    let pairs = Source::new(1, input).run(lines(separated_pair(id(), space1, id())))?;
+    Ok(pairs.into_iter().unzip())
 }
 #[aoc(day1, part1)]
-pub fn part1(input: &(Vec<usize>, Vec<usize>)) -> usize {
+pub fn part1(input: &(Vec<usize>, Vec<usize>)) -> Answer {
     input
         .0
         .iter()
         .sorted()
         .zip(input.1.iter().sorted())
         .fold(0, |acc, e| acc + e.0.abs_diff(*e.1))
+        .into()
 }
 
 #[aoc(day1, part2)]
-pub fn part2(input: &(Vec<usize>, Vec<usize>)) -> usize {
+pub fn part2(input: &(Vec<usize>, Vec<usize>)) -> Answer {
     let hm = input.1.iter().fold(HashMap::default(), |mut acc, l| {
         acc.insert(l, 1 + acc.get(&l).unwrap_or(&0));
         acc
@@ -29,6 +35,54 @@ pub fn part2(input: &(Vec<usize>, Vec<usize>)) -> usize {
         .0
         .iter()
         .fold(0, |acc, l| acc + l * hm.get(l).unwrap_or(&0))
+        .into()
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Parsed = (Vec<usize>, Vec<usize>);
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    impl Reference for Day1 {
+        /// Pairs up the smallest remaining ids until both lists are empty.
+        fn part1((left, right): &Self::Parsed, _: &()) -> Answer {
+            let (mut left, mut right) = (left.clone(), right.clone());
+            let mut distance = 0;
+            while let (Some(l), Some(r)) = (left.iter().position_min(), right.iter().position_min())
+            {
+                distance += left.swap_remove(l).abs_diff(right.swap_remove(r));
+            }
+            distance.into()
+        }
+
+        fn part2((left, right): &Self::Parsed, _: &()) -> Answer {
+            left.iter()
+                .map(|l| l * right.iter().filter(|&r| r == l).count())
+                .sum::<usize>()
+                .into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -42,10 +96,11 @@ mod tests {
 3   3";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 11)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(11))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 31)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(31))
     }
 }
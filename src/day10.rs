@@ -1,104 +1,140 @@
-use rustc_hash::FxHashSet as HashSet;
-use std::collections::BinaryHeap;
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::par::*;
+use crate::pos::Pos;
+use crate::search::{self, Paths};
+use crate::solution::Solution;
 
 pub struct Input {
-    grid: Vec<Vec<usize>>,
-    heads: Vec<(usize, usize)>,
-    h: usize,
-    w: usize,
+    grid: Grid<usize>,
+    heads: Vec<Pos>,
 }
 #[aoc_generator(day10)]
-pub fn parse(input: &str) -> Input {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let mut heads = Vec::new();
-    let grid: Vec<Vec<usize>> = input
-        .lines()
-        .enumerate()
-        .map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(j, c)| {
-                    if c == '0' {
-                        heads.push((i, j));
-                    }
-                    c.to_digit(10).unwrap() as usize
-                })
-                .collect()
-        })
-        .collect();
+    let grid = Source::new(10, input).grid(input, "a height digit", |p, c| {
+        if c == '0' {
+            heads.push(p);
+        }
+        c.to_digit(10).map(|d| d as usize)
+    })?;
 
-    Input {
-        h: grid.len(),
-        w: grid[0].len(),
-        grid,
-        heads,
-    }
+    Ok(Input { grid, heads })
+}
+
+/// Trails from one head, every step climbing by one.
+fn trails(input: &Input, head: Pos) -> Paths<Pos> {
+    search::bfs([head], |&p| {
+        input
+            .grid
+            .neighbours4(p)
+            .filter(move |&np| input.grid[np] == input.grid[p] + 1)
+    })
 }
 
-const DIRS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+fn tops(input: &Input, paths: &Paths<Pos>) -> impl Iterator<Item = Pos> {
+    paths
+        .distances()
+        .filter(|&(&p, _)| input.grid[p] == 9)
+        .map(|(&p, _)| p)
+}
 
 #[aoc(day10, part1)]
-pub fn part1(input: &Input) -> usize {
-    input.heads.iter().fold(0, |mut acc, (hi, hj)| {
-        let mut heap = BinaryHeap::new();
-        let mut visited = HashSet::default();
-        heap.push((*hi, *hj));
-        loop {
-            if let Some((pi, pj)) = heap.pop() {
-                if input.grid[pi][pj] == 9 {
-                    if !visited.contains(&(pi, pj)) {
-                        acc += 1;
-                        visited.insert((pi, pj));
-                    }
-                    continue;
-                }
-                for d in DIRS {
-                    let (npi, npj) = (pi.wrapping_add_signed(d.0), pj.wrapping_add_signed(d.1));
-                    if npi >= input.h || npj >= input.w {
-                        continue;
-                    }
-                    if input.grid[npi][npj] != input.grid[pi][pj] + 1 {
-                        continue;
-                    }
-                    heap.push((npi, npj));
-                }
-            } else {
-                break;
-            }
-        }
-        acc
-    })
+pub fn part1(input: &Input) -> Answer {
+    input
+        .heads
+        .par_iter()
+        .map(|&head| tops(input, &trails(input, head)).count())
+        .sum::<usize>()
+        .into()
 }
+
 #[aoc(day10, part2)]
-pub fn part2(input: &Input) -> usize {
-    input.heads.iter().fold(0, |mut acc, (hi, hj)| {
-        let mut heap = BinaryHeap::new();
-        heap.push((*hi, *hj));
-        loop {
-            if let Some((pi, pj)) = heap.pop() {
-                if input.grid[pi][pj] == 9 {
-                    acc += 1;
-                    continue;
-                }
-                for d in DIRS {
-                    let (npi, npj) = (pi.wrapping_add_signed(d.0), pj.wrapping_add_signed(d.1));
-                    if npi >= input.h || npj >= input.w {
-                        continue;
-                    }
-                    if input.grid[npi][npj] != input.grid[pi][pj] + 1 {
-                        continue;
-                    }
-                    heap.push((npi, npj));
-                }
-            } else {
-                break;
+pub fn part2(input: &Input) -> Answer {
+    input
+        .heads
+        .par_iter()
+        .map(|&head| {
+            let paths = trails(input, head);
+            tops(input, &paths)
+                .map(|p| paths.count_paths(&p))
+                .sum::<usize>()
+        })
+        .sum::<usize>()
+        .into()
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Tops at the end of every trail from `p`, once per trail.
+    fn climb(grid: &Grid<usize>, p: Pos, tops: &mut Vec<Pos>) {
+        if grid[p] == 9 {
+            tops.push(p);
+            return;
+        }
+        for np in grid.neighbours4(p) {
+            if grid[np] == grid[p] + 1 {
+                climb(grid, np, tops);
             }
         }
-        acc
-    })
+    }
+
+    fn trails(input: &Input) -> impl Iterator<Item = Vec<Pos>> + '_ {
+        input.heads.iter().map(|&head| {
+            let mut tops = Vec::new();
+            climb(&input.grid, head, &mut tops);
+            tops.sort();
+            tops
+        })
+    }
+
+    impl Reference for Day10 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            trails(input)
+                .map(|mut tops| {
+                    tops.dedup();
+                    tops.len()
+                })
+                .sum::<usize>()
+                .into()
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            trails(input).map(|tops| tops.len()).sum::<usize>().into()
+        }
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
     const INPUT: &str = "89010123
 78121874
 87430965
@@ -109,10 +145,21 @@ mod tests {
 10456732";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 36)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(36))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 81)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(81))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn part1_below_part2(seed: u64, size in 1usize..30) {
+            let input = parse(&generate::day10(&mut rng(seed), size)).unwrap();
+            prop_assert!(part1(&input).int() <= part2(&input).int());
+        }
     }
 }
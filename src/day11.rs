@@ -1,6 +1,21 @@
+use nom::combinator::opt;
 use rustc_hash::FxHashMap as HashMap;
 use std::collections::BinaryHeap;
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{number, spaced};
+use crate::solution::{Solution, params};
+
+params! {
+    pub struct Params {
+        /// Blinks of part 1.
+        pub blinks1: usize = 25,
+        /// Blinks of part 2.
+        pub blinks2: usize = 75,
+    }
+}
+
 pub struct Input {
     numbers: Vec<usize>,
     cache: HashMap<usize, Blink>,
@@ -12,17 +27,16 @@ enum Blink {
 }
 
 #[aoc_generator(day11)]
-pub fn parse(input: &str) -> Input {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let mut cache = HashMap::default();
-    let numbers: Vec<usize> = input
-        .split_whitespace()
-        .map(|l| l.parse::<usize>().unwrap())
-        .collect();
+    let numbers: Vec<usize> = Source::new(11, input)
+        .run(opt(spaced(number("a stone number"))))?
+        .unwrap_or_default();
 
     let mut to_check: BinaryHeap<usize> = numbers.iter().copied().collect();
 
     while let Some(mut n) = to_check.pop() {
-        while cache.get(&n).is_none() {
+        while !cache.contains_key(&n) {
             if n == 0 {
                 cache.insert(n, Blink::Change(1));
                 n = 1;
@@ -38,20 +52,23 @@ pub fn parse(input: &str) -> Input {
                 to_check.push(right);
             } else {
                 cache.insert(n, Blink::Change(n * 2024));
-                n = n * 2024;
+                n *= 2024;
             }
         }
     }
-    Input { numbers, cache }
+    Ok(Input { numbers, cache })
 }
 
 fn solve(input: &Input, n: usize) -> usize {
     (0..n)
         .fold(
-            input.numbers.iter().map(|n| (n, 1)).collect(),
+            input.numbers.iter().fold(HashMap::default(), |mut acc, n| {
+                *acc.entry(n).or_insert(0) += 1;
+                acc
+            }),
             |acc: HashMap<&usize, usize>, _| {
                 acc.iter().fold(HashMap::default(), |mut acc, (n, c)| {
-                    match input.cache.get(&n).unwrap() {
+                    match input.cache.get(n).unwrap() {
                         Blink::Change(d) => *acc.entry(d).or_insert(0) += c,
                         Blink::Split(l, r) => {
                             *acc.entry(l).or_insert(0) += c;
@@ -67,21 +84,107 @@ fn solve(input: &Input, n: usize) -> usize {
 }
 
 #[aoc(day11, part1)]
-pub fn part1(input: &Input) -> usize {
-    solve(input, 25)
+pub fn part1(input: &Input) -> Answer {
+    Day11::part1(input, &Params::default())
 }
 
 #[aoc(day11, part2)]
-pub fn part2(input: &Input) -> usize {
-    solve(input, 75)
+pub fn part2(input: &Input) -> Answer {
+    Day11::part2(input, &Params::default())
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        solve(input, params.blinks1).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        solve(input, params.blinks2).into()
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Number of stones after `blinks`, keeping every stone in line.
+    fn stones(stones: &[usize], blinks: usize) -> usize {
+        let mut stones = stones.to_vec();
+        for _ in 0..blinks {
+            stones = stones
+                .iter()
+                .flat_map(|&n| {
+                    let digits = n.to_string();
+                    if n == 0 {
+                        vec![1]
+                    } else if digits.len() % 2 == 0 {
+                        let (l, r) = digits.split_at(digits.len() / 2);
+                        vec![l.parse().unwrap(), r.parse().unwrap()]
+                    } else {
+                        vec![n * 2024]
+                    }
+                })
+                .collect();
+        }
+        stones.len()
+    }
+
+    impl Reference for Day11 {
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            stones(&input.numbers, params.blinks1).into()
+        }
+
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            stones(&input.numbers, params.blinks2).into()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
     const INPUT: &str = "125 17";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 55312)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(55312))
+    }
+
+    #[test]
+    fn example2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(65601038650482))
+    }
+
+    #[test]
+    fn repeated_stones() {
+        assert_eq!(part1(&parse("125 125").unwrap()), Answer::Int(38050))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn naive_blinks(seed: u64, size in 0usize..8, blinks in 0usize..15) {
+            let input = parse(&generate::day11(&mut rng(seed), size)).unwrap();
+            let params = Params { blinks1: blinks, ..Params::default() };
+            prop_assert_eq!(
+                <Day11 as Solution>::part1(&input, &params),
+                <Day11 as Reference>::part1(&input, &params)
+            );
+        }
     }
 }
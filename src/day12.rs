@@ -3,35 +3,32 @@ use std::collections::VecDeque;
 use itertools::Itertools;
 use rustc_hash::FxHashSet as HashSet;
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::{DIRS4, Grid};
+use crate::pos::{Pos, Vec2};
+use crate::solution::Solution;
+
 #[derive(Debug, Clone)]
 pub struct Input {
-    grid: Vec<Vec<char>>,
-    h: usize,
-    w: usize,
-    seen: HashSet<(usize, usize)>,
+    grid: Grid<char>,
+    seen: HashSet<Pos>,
 }
 
 impl Input {
-    pub fn get_around(&self, p: (usize, usize)) -> Vec<(usize, usize)> {
-        [
-            (p.0 + 1, p.1),
-            (p.0.wrapping_sub_signed(1), p.1),
-            (p.0, p.1 + 1),
-            (p.0, p.1.wrapping_sub_signed(1)),
-        ]
-        .iter()
-        .copied()
-        .filter(|pk| pk.0 < self.h && pk.1 < self.w && self.grid[pk.0][pk.1] == self.grid[p.0][p.1])
-        .collect()
+    pub fn get_around(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.grid
+            .neighbours4(p)
+            .filter(move |&pk| self.grid[pk] == self.grid[p])
     }
 
-    pub fn find_island(&mut self, p: (usize, usize)) -> HashSet<(usize, usize)> {
+    pub fn find_island(&mut self, p: Pos) -> HashSet<Pos> {
         let mut q = VecDeque::new();
         let mut island = HashSet::default();
         q.push_back(p);
         island.insert(p);
         while let Some(p) = q.pop_front() {
-            for pk in self.get_around(p) {
+            for pk in self.get_around(p).collect_vec() {
                 if self.seen.insert(pk) {
                     island.insert(pk);
                     q.push_back(pk);
@@ -43,80 +40,205 @@ impl Input {
 }
 
 #[aoc_generator(day12)]
-pub fn parse(input: &str) -> Input {
-    let grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
-    Input {
-        h: grid.len(),
-        w: grid[0].len(),
-        grid,
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    Ok(Input {
+        grid: Source::new(12, input).grid(input, "a plant letter", |_, c| {
+            c.is_ascii_uppercase().then_some(c)
+        })?,
         seen: HashSet::default(),
-    }
+    })
 }
 
-#[aoc(day12, part1)]
-pub fn part1(input: &Input) -> usize {
-    let mut input = input.clone();
-    (0..input.h)
-        .cartesian_product(0..input.w)
-        .fold(0, |acc, p| {
-            if input.seen.contains(&p) {
+/// Number of fences around `island`.
+fn perimeter(input: &Input, island: &HashSet<Pos>) -> usize {
+    island
+        .iter()
+        .map(|p| 4 - input.get_around(*p).count())
+        .sum()
+}
+
+/// Number of straight sides of the fence around `island`.
+fn sides(input: &Input, island: &HashSet<Pos>) -> usize {
+    let in_island = |p: Option<Pos>| p.is_some_and(|p| island.contains(&p));
+    island
+        .iter()
+        .cartesian_product(DIRS4)
+        .fold(HashSet::default(), |mut acc, (p, dp)| {
+            if in_island(input.grid.step(*p, dp)) {
                 return acc;
             }
-            let island = input.find_island(p);
-            acc + island.len()
-                * island
-                    .iter()
-                    .map(|p| 4 - input.get_around(*p).len())
-                    .sum::<usize>()
+            let mut pk = *p;
+            loop {
+                let p_angle = input.grid.step(pk, dp);
+                let p_next = input.grid.step(pk, Vec2::new(dp.y, dp.x));
+                if in_island(p_angle) || !in_island(p_next) {
+                    break;
+                }
+                pk = p_next.unwrap();
+            }
+            acc.insert((pk, dp));
+            acc
         })
+        .len()
 }
-#[aoc(day12, part2)]
-pub fn part2(input: &Input) -> usize {
+
+/// Sum over the regions of their area times `cost`.
+fn price(input: &Input, cost: impl Fn(&Input, &HashSet<Pos>) -> usize) -> usize {
     let mut input = input.clone();
-    (0..input.h)
-        .cartesian_product(0..input.w)
-        .fold(0, |acc, p| {
-            if input.seen.contains(&p) {
-                return acc;
+    input.grid.positions().fold(0, |acc, p| {
+        if input.seen.contains(&p) {
+            return acc;
+        }
+        let island = input.find_island(p);
+        acc + island.len() * cost(&input, &island)
+    })
+}
+
+/// Region of the garden, its plots sorted by row then column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub plant: char,
+    pub plots: Vec<Pos>,
+    pub perimeter: usize,
+    pub sides: usize,
+}
+
+/// Every region, in the order of their first plot.
+pub fn regions(input: &Input) -> Vec<Region> {
+    let mut input = input.clone();
+    let mut regions = Vec::new();
+    for p in input.grid.positions() {
+        if input.seen.contains(&p) {
+            continue;
+        }
+        let island = input.find_island(p);
+        regions.push(Region {
+            plant: input.grid[p],
+            plots: island
+                .iter()
+                .copied()
+                .sorted_by_key(|p| (p.y, p.x))
+                .collect(),
+            perimeter: perimeter(&input, &island),
+            sides: sides(&input, &island),
+        });
+    }
+    regions
+}
+
+#[aoc(day12, part1)]
+pub fn part1(input: &Input) -> Answer {
+    price(input, perimeter).into()
+}
+#[aoc(day12, part2)]
+pub fn part2(input: &Input) -> Answer {
+    price(input, sides).into()
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Regions of the garden, found by flood filling from every plot.
+    fn regions(grid: &Grid<char>) -> Vec<Vec<Pos>> {
+        let mut region = Grid::new(grid.width(), grid.height(), None);
+        let mut regions = Vec::new();
+        for p in grid.positions() {
+            if region[p].is_some() {
+                continue;
             }
-            let island = input.find_island(p);
-            acc + island.len()
-                * island
-                    .iter()
-                    .cartesian_product([(0, 1), (0, -1), (1, 0), (-1, 0)])
-                    .fold(HashSet::default(), |mut acc, (p, dp)| {
-                        if island.contains(&(
-                            p.0.wrapping_add_signed(dp.0),
-                            p.1.wrapping_add_signed(dp.1),
-                        )) {
-                            return acc;
-                        }
-                        let mut pk = *p;
-                        loop {
-                            let p_angle = (
-                                pk.0.wrapping_add_signed(dp.0),
-                                pk.1.wrapping_add_signed(dp.1),
-                            );
-                            let p_next = (
-                                pk.0.wrapping_add_signed(dp.1),
-                                pk.1.wrapping_add_signed(dp.0),
-                            );
-                            if island.contains(&p_angle) || !island.contains(&p_next) {
-                                break;
-                            }
-                            pk.0 = pk.0.wrapping_add_signed(dp.1);
-                            pk.1 = pk.1.wrapping_add_signed(dp.0);
-                        }
-                        acc.insert((pk, dp));
-                        acc
-                    })
-                    .len()
-        })
+            let mut plots = vec![p];
+            let mut stack = vec![p];
+            region[p] = Some(regions.len());
+            while let Some(p) = stack.pop() {
+                for np in grid.neighbours4(p) {
+                    if grid[np] == grid[p] && region[np].is_none() {
+                        region[np] = Some(regions.len());
+                        plots.push(np);
+                        stack.push(np);
+                    }
+                }
+            }
+            regions.push(plots);
+        }
+        regions
+    }
+
+    /// Whether `p` moved by `(dx, dy)` is a plot of the same plant.
+    fn same(grid: &Grid<char>, p: Pos, dx: isize, dy: isize) -> bool {
+        grid.step(p, Vec2::new(dx, dy))
+            .is_some_and(|np| grid[np] == grid[p])
+    }
+
+    impl Reference for Day12 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            regions(&input.grid)
+                .iter()
+                .map(|plots| {
+                    let fences: usize = plots
+                        .iter()
+                        .map(|&p| {
+                            DIRS4
+                                .iter()
+                                .filter(|d| !same(&input.grid, p, d.x, d.y))
+                                .count()
+                        })
+                        .sum();
+                    plots.len() * fences
+                })
+                .sum::<usize>()
+                .into()
+        }
+
+        /// Counts corners, as many as there are sides.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let grid = &input.grid;
+            regions(grid)
+                .iter()
+                .map(|plots| {
+                    let corners: usize = plots
+                        .iter()
+                        .cartesian_product([(-1, -1), (-1, 1), (1, -1), (1, 1)])
+                        .filter(|&(&p, (dx, dy))| {
+                            let (x, y) = (same(grid, p, dx, 0), same(grid, p, 0, dy));
+                            (!x && !y) || (x && y && !same(grid, p, dx, dy))
+                        })
+                        .count();
+                    plots.len() * corners
+                })
+                .sum::<usize>()
+                .into()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
     const INPUT: &str = "RRRRIICCFF
 RRRRIICCCF
 VVRRRCCFFF
@@ -129,10 +251,36 @@ MIIISIJEEE
 MMMISSJEEE";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 1930)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(1930))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 1206)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(1206))
+    }
+
+    #[test]
+    fn regions() {
+        let regions = super::regions(&parse(INPUT).unwrap());
+        assert_eq!(regions.len(), 11);
+        assert_eq!(regions[0].plant, 'R');
+        assert_eq!(regions[0].plots.len(), 12);
+        assert_eq!(regions[0].plots[..2], [Pos::new(0, 0), Pos::new(1, 0)]);
+        assert_eq!((regions[0].perimeter, regions[0].sides), (18, 10));
+        let total = |f: fn(&Region) -> usize| -> usize {
+            regions.iter().map(|r| r.plots.len() * f(r)).sum()
+        };
+        assert_eq!(total(|r| r.perimeter), 1930);
+        assert_eq!(total(|r| r.sides), 1206);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn part2_below_part1(seed: u64, size in 1usize..30) {
+            let input = parse(&generate::day12(&mut rng(seed), size)).unwrap();
+            prop_assert!(part2(&input).int() <= part1(&input).int());
+        }
     }
 }
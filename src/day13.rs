@@ -1,3 +1,18 @@
+use nom::combinator::map;
+use nom::sequence::{preceded, separated_pair, terminated, tuple};
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{blocks, eol, number, tag};
+use crate::solution::{Solution, params};
+
+params! {
+    pub struct Params {
+        /// Added to both prize coordinates in part 2.
+        pub offset: isize = 10000000000000,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Game {
     a: (isize, isize),
@@ -8,64 +23,156 @@ pub struct Game {
 impl Game {
     // let's do some old school maths
     fn solve(&self) -> isize {
-        let k = (self.prize.1 * self.a.0 - self.prize.0 * self.a.1)
-            / (self.b.1 * self.a.0 - self.b.0 * self.a.1);
-        let j = (self.prize.0 - k * self.b.0) / self.a.0;
-        if self.a.0 * j + self.b.0 * k != self.prize.0
-            || self.a.1 * j + self.b.1 * k != self.prize.1
-        {
+        let det = self.a.0 * self.b.1 - self.a.1 * self.b.0;
+        if det == 0 {
+            return self.solve_collinear();
+        }
+        let j = self.prize.0 * self.b.1 - self.prize.1 * self.b.0;
+        let k = self.a.0 * self.prize.1 - self.a.1 * self.prize.0;
+        if j % det != 0 || k % det != 0 || j / det < 0 || k / det < 0 {
             return 0;
         }
-        j * 3 + k
+        j / det * 3 + k / det
+    }
+
+    /// Cheapest presses when both buttons move the claw along the same line.
+    fn solve_collinear(&self) -> isize {
+        let (a, b, p) = (self.a, self.b, self.prize);
+        if a.0 * p.1 != a.1 * p.0 {
+            return 0;
+        }
+        // Distances along the line, on an axis both buttons move forward on.
+        let (a, b, p) = if a.0 > 0 && b.0 > 0 {
+            (a.0, b.0, p.0)
+        } else if a.1 > 0 && b.1 > 0 {
+            (a.1, b.1, p.1)
+        } else {
+            return 0;
+        };
+        // As few presses as possible of the button moving less per token.
+        let presses = if 3 * b >= a {
+            fewest(a, b, p)
+        } else {
+            fewest(b, a, p).map(|(k, j)| (j, k))
+        };
+        presses.map_or(0, |(j, k)| 3 * j + k)
     }
 }
 
+/// Fewest presses of a button moving `x` so that whole presses of one moving
+/// `y` make up the rest of `p`, along with the number of those.
+fn fewest(x: isize, y: isize, p: isize) -> Option<(isize, isize)> {
+    // The remainder modulo `y` repeats after `y` presses.
+    (0..y)
+        .take_while(|n| n * x <= p)
+        .find(|n| (p - n * x) % y == 0)
+        .map(|n| (n, (p - n * x) / y))
+}
+
 #[derive(Debug, Clone)]
 pub struct Input {
     games: Vec<Game>,
 }
 
 #[aoc_generator(day13)]
-pub fn parse(input: &str) -> Input {
-    let games = input
-        .split("\n\n")
-        .map(|ll| {
-            let mut lines = ll.lines();
-            let a = lines.next().unwrap().strip_prefix("Button A: X+").unwrap();
-            let b = lines.next().unwrap().strip_prefix("Button B: X+").unwrap();
-            let prize = lines.next().unwrap().strip_prefix("Prize: X=").unwrap();
-
-            let a = a.split_once(", Y+").unwrap();
-            let b = b.split_once(", Y+").unwrap();
-            let prize = prize.split_once(", Y=").unwrap();
-
-            Game {
-                a: (a.0.parse().unwrap(), a.1.parse().unwrap()),
-                b: (b.0.parse().unwrap(), b.1.parse().unwrap()),
-                prize: (prize.0.parse().unwrap(), prize.1.parse().unwrap()),
-            }
-        })
-        .collect();
-
-    Input { games }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let n = || number("a number");
+    let button = |name| separated_pair(preceded(tag(name), n()), tag(", Y+"), n());
+    let prize = separated_pair(preceded(tag("Prize: X="), n()), tag(", Y="), n());
+    let game = map(
+        tuple((
+            terminated(button("Button A: X+"), eol),
+            terminated(button("Button B: X+"), eol),
+            prize,
+        )),
+        |(a, b, prize)| Game { a, b, prize },
+    );
+    Ok(Input {
+        games: Source::new(13, input).run(blocks(game))?,
+    })
 }
 
-#[aoc(day13, part1)]
-pub fn part1(input: &Input) -> isize {
-    input.games.iter().fold(0, |acc, g| acc + g.solve())
-}
-#[aoc(day13, part2)]
-pub fn part2(input: &Input) -> isize {
+fn tokens(input: &Input, offset: isize) -> isize {
     input.games.iter().fold(0, |acc, g| {
         let mut g = g.clone();
-        g.prize.0 += 10000000000000;
-        g.prize.1 += 10000000000000;
+        g.prize.0 += offset;
+        g.prize.1 += offset;
         acc + g.solve()
     })
 }
+
+#[aoc(day13, part1)]
+pub fn part1(input: &Input) -> Answer {
+    Day13::part1(input, &Params::default())
+}
+
+#[aoc(day13, part2)]
+pub fn part2(input: &Input) -> Answer {
+    Day13::part2(input, &Params::default())
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &Params) -> Answer {
+        tokens(input, 0).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        tokens(input, params.offset).into()
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Cheapest win trying every number of A presses, 0 if there is none.
+    fn cheapest(g: &Game, offset: isize) -> isize {
+        let prize = (g.prize.0 + offset, g.prize.1 + offset);
+        (0..=prize.0 / g.a.0)
+            .filter_map(|j| {
+                let rest = (prize.0 - j * g.a.0, prize.1 - j * g.a.1);
+                let k = rest.0 / g.b.0;
+                (rest.0 % g.b.0 == 0 && rest.1 == k * g.b.1).then_some(3 * j + k)
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    impl Reference for Day13 {
+        fn part1(input: &Self::Parsed, _: &Params) -> Answer {
+            input
+                .games
+                .iter()
+                .map(|g| cheapest(g, 0))
+                .sum::<isize>()
+                .into()
+        }
+
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            let tokens: isize = input.games.iter().map(|g| cheapest(g, params.offset)).sum();
+            tokens.into()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
     const INPUT: &str = "Button A: X+94, Y+34
 Button B: X+22, Y+67
 Prize: X=8400, Y=5400
@@ -83,6 +190,47 @@ Button B: X+27, Y+71
 Prize: X=18641, Y=10279";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 480)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(480))
+    }
+
+    #[test]
+    fn example2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(875318608908))
+    }
+    #[test]
+    fn bad_input() {
+        let err =
+            parse("Button A: X+94, Y+34\nButton C: X+22, Y+67\nPrize: X=8400, Y=5400").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, "\"Button B: X+\"");
+        let err = parse("Button A: X+94, Y+34\nButton B: X+22, Y+67").unwrap_err();
+        assert_eq!((err.line, err.found.as_str()), (2, "end of input"));
+    }
+
+    #[test]
+    fn negative_presses() {
+        let input = parse(&generate::day13(&mut rng(11294120094979839733), 4)).unwrap();
+        assert_eq!(part1(&input), Answer::Int(276));
+    }
+
+    #[test]
+    fn collinear() {
+        let input = parse("Button A: X+1, Y+1\nButton B: X+2, Y+2\nPrize: X=4, Y=4").unwrap();
+        assert_eq!(part1(&input), Answer::Int(2));
+        let input = parse("Button A: X+1, Y+1\nButton B: X+2, Y+2\nPrize: X=4, Y=5").unwrap();
+        assert_eq!(part1(&input), Answer::Int(0));
+        let input = parse("Button A: X+9, Y+9\nButton B: X+2, Y+2\nPrize: X=13, Y=13").unwrap();
+        // Two presses of B move 4 for 2 tokens, one of A moves 9 for 3.
+        assert_eq!(part1(&input), Answer::Int(3 + 2));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn naive_presses(seed: u64, size in 1usize..10) {
+            let input = parse(&generate::day13(&mut rng(seed), size)).unwrap();
+            prop_assert_eq!(part1(&input), <Day13 as Reference>::part1(&input, &Params::default()));
+        }
     }
 }
@@ -1,64 +1,74 @@
 use itertools::Itertools;
-use std::cmp::max;
+use nom::character::complete::space1;
+use nom::combinator::map;
+use nom::sequence::separated_pair;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::parsing::{field, lines, xy};
+use crate::pos::{Bounds, Pos, Vec2};
+use crate::solution::{Solution, params};
+use crate::viz::{Animate, Frame, Glyph, Rgb};
+
+params! {
+    pub struct Params {
+        pub width: usize = 101,
+        pub height: usize = 103,
+        /// Elapsed time of part 1.
+        pub seconds: isize = 100,
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Robot {
-    p: (isize, isize),
-    v: (isize, isize),
+    p: Vec2,
+    v: Vec2,
 }
 
 #[derive(Debug, Clone)]
 pub struct Input {
     robots: Vec<Robot>,
-    w: usize,
-    h: usize,
 }
 
 #[aoc_generator(day14)]
-pub fn parse(input: &str) -> Input {
-    let mut w = 0;
-    let mut h = 0;
-    Input {
-        robots: input
-            .lines()
-            .map(|l| {
-                let (l, r) = l.split_once(" ").unwrap();
-                let p = l.split_once("=").unwrap().1.split_once(",").unwrap();
-                let p = (p.0.parse().unwrap(), p.1.parse().unwrap());
-                let v = r.split_once("=").unwrap().1.split_once(",").unwrap();
-                let v = (v.0.parse().unwrap(), v.1.parse().unwrap());
-                w = max(w, p.0);
-                h = max(h, p.1);
-
-                Robot { p, v }
-            })
-            .collect(),
-        w: w as usize + 1,
-        h: h as usize + 1,
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let robot = map(
+        separated_pair(
+            field("p=", xy("a number")),
+            space1,
+            field("v=", xy("a number")),
+        ),
+        |((px, py), (vx, vy))| Robot {
+            p: Vec2::new(px, py),
+            v: Vec2::new(vx, vy),
+        },
+    );
+    Ok(Input {
+        robots: Source::new(14, input).run(lines(robot))?,
+    })
 }
 
 #[aoc(day14, part1)]
-pub fn part1(input: &Input) -> usize {
+pub fn part1(input: &Input) -> Answer {
+    Day14::part1(input, &Params::default())
+}
+
+fn safety_factor(input: &Input, params: &Params) -> usize {
+    let bounds = Bounds::new(params.width, params.height);
+    let (w, h) = (bounds.w / 2, bounds.h / 2);
     let (a, b, c, d) = input.robots.iter().fold((0, 0, 0, 0), |mut acc, r| {
-        let mut x = r.p.0 + r.v.0 * 100;
-        let mut y = r.p.1 + r.v.1 * 100;
-        if x >= input.w as isize || x < 0 {
-            x = x.rem_euclid(input.w as isize);
-        }
-        if y >= input.h as isize || y < 0 {
-            y = y.rem_euclid(input.h as isize);
-        }
-        if x > input.w as isize / 2 {
-            if y > input.h as isize / 2 {
+        let Pos { x, y } = (r.p + r.v * params.seconds).wrap(bounds);
+        if x > w {
+            if y > h {
                 acc.0 += 1;
-            } else if y < input.h as isize / 2 {
+            } else if y < h {
                 acc.1 += 1;
             }
-        } else if x < input.w as isize / 2 {
-            if y > input.h as isize / 2 {
+        } else if x < w {
+            if y > h {
                 acc.2 += 1;
-            } else if y < input.h as isize / 2 {
+            } else if y < h {
                 acc.3 += 1;
             }
         };
@@ -68,24 +78,140 @@ pub fn part1(input: &Input) -> usize {
 }
 
 #[aoc(day14, part2)]
-pub fn part2(input: &Input) -> usize {
+pub fn part2(input: &Input) -> Answer {
+    Day14::part2(input, &Params::default())
+}
+
+/// First second with every robot on its own tile, 0 if there is none.
+fn tree(input: &Input, params: &Params) -> usize {
+    let bounds = Bounds::new(params.width, params.height);
     let mut robots = input.robots.clone();
-    for i in 1.. {
+    // The robots are back where they started after `width * height` seconds.
+    for i in 1..=bounds.w * bounds.h {
         robots.iter_mut().for_each(|r| {
-            r.p.0 += r.v.0;
-            r.p.1 += r.v.1;
-            if r.p.0 >= input.w as isize || r.p.0 < 0 {
-                r.p.0 = r.p.0.rem_euclid(input.w as isize);
-            }
-            if r.p.1 >= input.h as isize || r.p.1 < 0 {
-                r.p.1 = r.p.1.rem_euclid(input.h as isize);
-            }
+            r.p = (r.p + r.v).wrap(bounds).to_vec();
         });
         if robots.iter().map(|r| r.p).all_unique() {
             return i;
         }
     }
-    unreachable!();
+    0
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        safety_factor(input, params).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        tree(input, params).into()
+    }
+
+    fn check(_: &Self::Parsed, params: &Params) -> Result<(), String> {
+        if params.width == 0 || params.height == 0 {
+            return Err(format!(
+                "the space is {}x{}, expected at least one tile",
+                params.width, params.height
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Robots moving second by second, until the elapsed time of part 1 or the
+/// tree of part 2.
+impl Animate for Day14 {
+    fn frames<'a>(
+        input: &'a Input,
+        params: &'a Params,
+        part: u8,
+    ) -> Box<dyn Iterator<Item = Frame> + 'a> {
+        let bounds = Bounds::new(params.width, params.height);
+        let end = if part == 1 {
+            params.seconds
+        } else {
+            tree(input, params) as isize
+        };
+        Box::new((0..=end).map(move |t| {
+            let mut robots = Grid::new(bounds.w, bounds.h, 0);
+            for r in &input.robots {
+                robots[(r.p + r.v * t).wrap(bounds)] += 1;
+            }
+            Frame::new(&robots, |&n| match n {
+                0 => Glyph::new('.', Rgb::DARK),
+                n => Glyph::new(char::from_digit(n.min(9), 10).unwrap(), Rgb::GREEN),
+            })
+        }))
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use rustc_hash::FxHashSet;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Robots after moving one second at a time.
+    fn walk(input: &Input, bounds: Bounds, seconds: isize) -> Vec<Pos> {
+        input
+            .robots
+            .iter()
+            .map(|r| {
+                let v = r.v * seconds.signum();
+                (0..seconds.unsigned_abs())
+                    .fold(r.p.wrap(bounds), |p, _| (p.to_vec() + v).wrap(bounds))
+            })
+            .collect()
+    }
+
+    impl Reference for Day14 {
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            let bounds = Bounds::new(params.width, params.height);
+            let robots = walk(input, bounds, params.seconds);
+            let (mid_x, mid_y) = (bounds.w / 2, bounds.h / 2);
+            [(false, false), (false, true), (true, false), (true, true)]
+                .iter()
+                .map(|&(right, down)| {
+                    robots
+                        .iter()
+                        .filter(|p| p.x != mid_x && p.y != mid_y)
+                        .filter(|p| (p.x > mid_x) == right && (p.y > mid_y) == down)
+                        .count()
+                })
+                .product::<usize>()
+                .into()
+        }
+
+        /// First second with every robot on its own tile, 0 if there is none
+        /// before they are all back.
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            let bounds = Bounds::new(params.width, params.height);
+            let mut robots = walk(input, bounds, 0);
+            for t in 1..=bounds.w * bounds.h {
+                robots = robots
+                    .iter()
+                    .zip(&input.robots)
+                    .map(|(p, r)| (p.to_vec() + r.v).wrap(bounds))
+                    .collect();
+                if robots.iter().collect::<FxHashSet<_>>().len() == robots.len() {
+                    return t.into();
+                }
+            }
+            0.into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -105,6 +231,42 @@ p=2,4 v=2,-3
 p=9,5 v=-3,-3";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 12)
+        let params = Params {
+            width: 11,
+            height: 7,
+            ..Params::default()
+        };
+        assert_eq!(
+            Day14::part1(&parse(INPUT).unwrap(), &params),
+            Answer::Int(12)
+        )
+    }
+
+    #[test]
+    fn example2() {
+        // The second robot stands where the first one is after one second.
+        let input = parse("p=0,0 v=1,0\np=1,0 v=0,0\np=10,6 v=0,0").unwrap();
+        let params = Params {
+            width: 11,
+            height: 7,
+            ..Params::default()
+        };
+        assert_eq!(Day14::part2(&input, &params), Answer::Int(2))
+    }
+
+    #[test]
+    fn no_tree() {
+        // Both robots always share a tile.
+        let input = parse("p=0,0 v=1,2\np=0,0 v=1,2").unwrap();
+        let params = Params {
+            width: 3,
+            height: 5,
+            ..Params::default()
+        };
+        assert_eq!(Day14::part2(&input, &params), Answer::Int(0));
+        assert_eq!(
+            <Day14 as crate::reference::Reference>::part2(&input, &params),
+            Answer::Int(0)
+        );
     }
 }
@@ -1,21 +1,14 @@
-#[derive(Debug, Clone, Copy)]
-pub enum Move {
-    Up,
-    Down,
-    Right,
-    Left,
-}
+use nom::sequence::separated_pair;
 
-impl Move {
-    fn get_dir(&self) -> (isize, isize) {
-        match self {
-            Move::Down => (1, 0),
-            Move::Up => (-1, 0),
-            Move::Left => (0, -1),
-            Move::Right => (0, 1),
-        }
-    }
-}
+use crate::answer::Answer;
+use crate::direction::Direction;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::parsing::{blank_line, char_map, lines, row};
+use crate::pos::{Pos, Vec2};
+use crate::solution::Solution;
+use crate::trace::{debug, trace};
+use crate::viz::{Animate, Frame, Glyph, Rgb};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub enum Cell {
@@ -26,132 +19,118 @@ pub enum Cell {
 
 #[derive(Debug, Clone)]
 pub struct Input {
-    grid: Vec<Vec<Cell>>,
-    moves: Vec<Move>,
-    robot_pos: (usize, usize),
+    grid: Grid<Cell>,
+    moves: Vec<Direction>,
+    robot_pos: Pos,
 }
 
 #[aoc_generator(day15)]
-pub fn parse(input: &str) -> Input {
-    let (first, sec) = input.split_once("\n\n").unwrap();
-    let mut robot_pos = (0, 0);
-    let grid = first
-        .lines()
-        .enumerate()
-        .map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(j, c)| match c {
-                    '#' => Cell::Wall,
-                    '.' => Cell::Empty,
-                    'O' => Cell::Box(false),
-                    '@' => {
-                        robot_pos = (i, j);
-                        Cell::Empty
-                    }
-                    _ => unreachable!(),
-                })
-                .collect()
-        })
-        .collect();
-    Input {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(15, input);
+    let mut robot_pos = None;
+    let warehouse = char_map("one of #.O@", |p, c| match c {
+        '#' => Some(Cell::Wall),
+        '.' => Some(Cell::Empty),
+        'O' => Some(Cell::Box(false)),
+        '@' => {
+            robot_pos = Some(p);
+            Some(Cell::Empty)
+        }
+        _ => None,
+    });
+    let moves = lines(row("a move", Direction::from_arrow));
+    let (grid, moves) = src.run(separated_pair(warehouse, blank_line, moves))?;
+    Ok(Input {
         grid,
-        robot_pos,
-        moves: sec
-            .chars()
-            .filter_map(|c| match c {
-                '>' => Some(Move::Right),
-                '<' => Some(Move::Left),
-                '^' => Some(Move::Up),
-                'v' => Some(Move::Down),
-                _ => None,
-            })
-            .collect(),
+        robot_pos: robot_pos.ok_or_else(|| src.error(input, "a robot in the map"))?,
+        moves: moves.concat(),
+    })
+}
+
+/// Robot position after trying to move it, pushing the boxes in the way.
+fn push(grid: &mut Grid<Cell>, p: Pos, m: Direction) -> Pos {
+    trace!(robot = %p, direction = ?m, "robot move");
+    let dp = m.delta();
+    let Some(next) = grid.step(p, dp) else {
+        return p;
+    };
+    match grid[next] {
+        Cell::Empty => next,
+        Cell::Wall => p,
+        Cell::Box(_) => {
+            let mut tmp = next;
+            while let Cell::Box(_) = grid[tmp] {
+                match grid.step(tmp, dp) {
+                    Some(n) => tmp = n,
+                    None => return p,
+                }
+            }
+            if let Cell::Empty = grid[tmp] {
+                debug!(from = %next, to = %tmp, "boxes pushed");
+                grid[tmp] = Cell::Box(false);
+                grid[next] = Cell::Empty;
+                next
+            } else {
+                p
+            }
+        }
     }
 }
 
 #[aoc(day15, part1)]
-pub fn part1(input: &Input) -> usize {
+pub fn part1(input: &Input) -> Answer {
     let mut p = input.robot_pos;
     let mut grid = input.grid.clone();
-    input.moves.iter().for_each(|m| {
-        let dp = m.get_dir();
-        let next = (p.0.wrapping_add_signed(dp.0), p.1.wrapping_add_signed(dp.1));
-        match grid[next.0][next.1] {
-            Cell::Empty => p = next,
-            Cell::Wall => (),
-            Cell::Box(_) => {
-                let mut tmp = next;
-                while let Cell::Box(_) = grid[tmp.0][tmp.1] {
-                    tmp = (
-                        tmp.0.wrapping_add_signed(dp.0),
-                        tmp.1.wrapping_add_signed(dp.1),
-                    );
-                }
-                if let Cell::Empty = grid[tmp.0][tmp.1] {
-                    grid[tmp.0][tmp.1] = Cell::Box(false);
-                    grid[next.0][next.1] = Cell::Empty;
-                    p = next;
-                }
-            }
-        }
-    });
-    grid.iter().enumerate().fold(0, |acc, (i, r)| {
-        acc + r.iter().enumerate().fold(0, |acci, (j, c)| match c {
-            Cell::Box(_) => acci + 100 * i + j,
-            _ => acci,
+    for &m in &input.moves {
+        p = push(&mut grid, p, m);
+    }
+    grid.iter()
+        .fold(0, |acc, (p, c)| match c {
+            Cell::Box(_) => acc + 100 * p.y + p.x,
+            _ => acc,
         })
-    })
+        .into()
 }
 
-fn move_box(grid: &mut Vec<Vec<Cell>>, p_box: (usize, usize), dir: (isize, isize), is_left: bool) {
-    let next = (
-        p_box.0.wrapping_add_signed(dir.0),
-        p_box.1.wrapping_add_signed(dir.1),
-    );
+fn move_box(grid: &mut Grid<Cell>, p_box: Pos, dir: Direction, is_left: bool) {
+    let next = grid.step(p_box, dir.delta()).unwrap();
 
-    match grid[next.0][next.1] {
-        Cell::Empty => grid[next.0][next.1] = Cell::Box(is_left),
+    match grid[next] {
+        Cell::Empty => grid[next] = Cell::Box(is_left),
         Cell::Box(is_inner_left) => {
-            if dir.1 != 0 {
+            if dir.is_horizontal() {
                 move_box(grid, next, dir, is_inner_left);
-                grid[next.0][next.1] = Cell::Box(!is_inner_left);
+                grid[next] = Cell::Box(!is_inner_left);
                 return;
             }
             if is_inner_left == is_left {
                 move_box(grid, next, dir, is_left);
-                grid[next.0][next.1] = Cell::Box(is_inner_left);
+                grid[next] = Cell::Box(is_inner_left);
                 return;
             }
 
             move_box(grid, next, dir, is_inner_left);
             if is_inner_left {
-                move_box(grid, (next.0, next.1 + 1), dir, !is_inner_left);
-                grid[next.0][next.1 + 1] = Cell::Empty;
+                move_box(grid, next + Vec2::RIGHT, dir, !is_inner_left);
+                grid[next + Vec2::RIGHT] = Cell::Empty;
             } else {
-                move_box(grid, (next.0, next.1 - 1), dir, !is_inner_left);
-                grid[next.0][next.1 - 1] = Cell::Empty;
+                move_box(grid, next + Vec2::LEFT, dir, !is_inner_left);
+                grid[next + Vec2::LEFT] = Cell::Empty;
             }
-            grid[next.0][next.1] = Cell::Box(is_left);
+            grid[next] = Cell::Box(is_left);
         }
         _ => unreachable!(),
     }
 }
-fn can_move_box(
-    grid: &mut Vec<Vec<Cell>>,
-    p_box: (usize, usize),
-    dir: (isize, isize),
-    is_left: bool,
-) -> bool {
-    let next = (
-        p_box.0.wrapping_add_signed(dir.0),
-        p_box.1.wrapping_add_signed(dir.1),
-    );
+fn can_move_box(grid: &Grid<Cell>, p_box: Pos, dir: Direction, is_left: bool) -> bool {
+    let Some(next) = grid.step(p_box, dir.delta()) else {
+        return false;
+    };
 
-    match grid[next.0][next.1] {
+    match grid[next] {
         Cell::Empty => true,
         Cell::Box(is_inner_left) => {
-            if is_inner_left == is_left || dir.1 != 0 {
+            if is_inner_left == is_left || dir.is_horizontal() {
                 return can_move_box(grid, next, dir, is_inner_left);
             }
             let first = can_move_box(grid, next, dir, is_inner_left);
@@ -159,83 +138,200 @@ fn can_move_box(
                 return false;
             }
             if is_inner_left {
-                can_move_box(grid, (next.0, next.1 + 1), dir, !is_inner_left)
+                can_move_box(grid, next + Vec2::RIGHT, dir, !is_inner_left)
             } else {
-                can_move_box(grid, (next.0, next.1 - 1), dir, !is_inner_left)
+                can_move_box(grid, next + Vec2::LEFT, dir, !is_inner_left)
             }
         }
         Cell::Wall => false,
     }
 }
 
-#[aoc(day15, part2)]
-pub fn part2(input: &Input) -> usize {
-    let mut pos = (input.robot_pos.0, input.robot_pos.1 * 2);
-    let mut grid = input.grid.iter().fold(Vec::new(), |mut acc, r| {
-        acc.push(r.iter().fold(
-            Vec::with_capacity(input.grid[0].len() * 2),
-            |mut acci, c| {
-                match c {
-                    Cell::Empty => {
-                        acci.push(Cell::Empty);
-                        acci.push(Cell::Empty);
-                    }
-                    Cell::Box(_) => {
-                        acci.push(Cell::Box(true));
-                        acci.push(Cell::Box(false));
-                    }
-                    Cell::Wall => {
-                        acci.push(Cell::Wall);
-                        acci.push(Cell::Wall);
-                    }
+/// Warehouse twice as wide, and the robot in it.
+fn widen(input: &Input) -> (Grid<Cell>, Pos) {
+    let grid = Grid::from_vec(
+        input.grid.width() * 2,
+        input.grid.height(),
+        input
+            .grid
+            .iter()
+            .flat_map(|(_, c)| match c {
+                Cell::Empty => [Cell::Empty, Cell::Empty],
+                Cell::Box(_) => [Cell::Box(true), Cell::Box(false)],
+                Cell::Wall => [Cell::Wall, Cell::Wall],
+            })
+            .collect(),
+    );
+    (grid, Pos::new(input.robot_pos.x * 2, input.robot_pos.y))
+}
+
+/// Same as [`push`] in the wide warehouse.
+fn push_wide(grid: &mut Grid<Cell>, pos: Pos, m: Direction) -> Pos {
+    trace!(robot = %pos, direction = ?m, "robot move");
+    let Some(next) = grid.step(pos, m.delta()) else {
+        return pos;
+    };
+
+    match grid[next] {
+        Cell::Empty => next,
+        Cell::Box(is_left) => {
+            let other = if is_left {
+                next + Vec2::RIGHT
+            } else {
+                next + Vec2::LEFT
+            };
+            if m.is_horizontal() {
+                if can_move_box(grid, next, m, is_left) {
+                    debug!(from = %next, direction = ?m, "boxes pushed");
+                    move_box(grid, next, m, is_left);
+                    grid[next] = Cell::Empty;
+                    return next;
                 }
-                acci
-            },
-        ));
-        acc
-    });
-    input.moves.iter().for_each(|m| {
-        let dp = m.get_dir();
-        let next = (
-            pos.0.wrapping_add_signed(dp.0),
-            pos.1.wrapping_add_signed(dp.1),
-        );
+            } else if can_move_box(grid, next, m, is_left) && can_move_box(grid, other, m, !is_left)
+            {
+                debug!(from = %next, direction = ?m, "boxes pushed");
+                move_box(grid, next, m, is_left);
+                move_box(grid, other, m, !is_left);
+                grid[next] = Cell::Empty;
+                grid[other] = Cell::Empty;
+                return next;
+            }
+            pos
+        }
+        Cell::Wall => pos,
+    }
+}
 
-        match grid[next.0][next.1] {
-            Cell::Empty => pos = next,
-            Cell::Box(is_left) => {
-                let other = if is_left {
-                    (next.0, next.1 + 1)
+#[aoc(day15, part2)]
+pub fn part2(input: &Input) -> Answer {
+    let (mut grid, mut pos) = widen(input);
+    for &m in &input.moves {
+        pos = push_wide(&mut grid, pos, m);
+    }
+    grid.iter()
+        .fold(0, |acc, (p, c)| match c {
+            Cell::Box(true) => acc + 100 * p.y + p.x,
+            _ => acc,
+        })
+        .into()
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+fn warehouse(grid: &Grid<Cell>, robot: Pos, wide: bool) -> Frame {
+    Frame::new(grid, |c| match c {
+        Cell::Empty => Glyph::new('.', Rgb::DARK),
+        Cell::Wall => Glyph::new('#', Rgb::GREY),
+        Cell::Box(_) if !wide => Glyph::new('O', Rgb::YELLOW),
+        Cell::Box(true) => Glyph::new('[', Rgb::YELLOW),
+        Cell::Box(false) => Glyph::new(']', Rgb::YELLOW),
+    })
+    .path([robot], Glyph::new('@', Rgb::RED))
+}
+
+/// The robot pushing boxes move after move, in the wide warehouse for part 2.
+impl Animate for Day15 {
+    fn frames<'a>(input: &'a Input, _: &'a (), part: u8) -> Box<dyn Iterator<Item = Frame> + 'a> {
+        let wide = part == 2;
+        let (mut grid, mut p) = if wide {
+            widen(input)
+        } else {
+            (input.grid.clone(), input.robot_pos)
+        };
+        let first = warehouse(&grid, p, wide);
+        Box::new(
+            std::iter::once(first).chain(input.moves.iter().map(move |&m| {
+                p = if wide {
+                    push_wide(&mut grid, p, m)
                 } else {
-                    (next.0, next.1 - 1)
+                    push(&mut grid, p, m)
                 };
-                if dp.1 != 0 {
-                    if can_move_box(&mut grid, next, dp, is_left) {
-                        move_box(&mut grid, next, dp, is_left);
-                        grid[next.0][next.1] = Cell::Empty;
-                        pos = next;
-                    }
-                } else {
-                    if can_move_box(&mut grid, next, dp, is_left)
-                        && can_move_box(&mut grid, other, dp, !is_left)
-                    {
-                        move_box(&mut grid, next, dp, is_left);
-                        move_box(&mut grid, other, dp, !is_left);
-                        grid[next.0][next.1] = Cell::Empty;
-                        grid[other.0][other.1] = Cell::Empty;
-                        pos = next;
+                warehouse(&grid, p, wide)
+            })),
+        )
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use rustc_hash::FxHashSet;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Sum of the GPS coordinates of the boxes once the robot is done, every
+    /// tile of the map being `width` tiles wide.
+    fn warehouse(input: &Input, width: usize) -> usize {
+        let scale = |p: Pos| Pos::new(p.x * width, p.y);
+        let walls: FxHashSet<Pos> = input
+            .grid
+            .iter()
+            .filter(|&(_, c)| *c == Cell::Wall)
+            .flat_map(|(p, _)| (0..width).map(move |i| scale(p) + Vec2::new(i as isize, 0)))
+            .collect();
+        let mut boxes: Vec<Pos> = input
+            .grid
+            .iter()
+            .filter(|&(_, c)| matches!(c, Cell::Box(_)))
+            .map(|(p, _)| scale(p))
+            .collect();
+        let mut robot = scale(input.robot_pos);
+        for m in &input.moves {
+            let d = m.delta();
+            // Tiles to clear, growing with the boxes standing on them.
+            let mut tiles = vec![robot + d];
+            let mut pushed = vec![false; boxes.len()];
+            let mut blocked = false;
+            while let Some(t) = tiles.pop() {
+                if walls.contains(&t) {
+                    blocked = true;
+                    break;
+                }
+                for (i, b) in boxes.iter().enumerate() {
+                    if !pushed[i] && b.y == t.y && (b.x..b.x + width).contains(&t.x) {
+                        pushed[i] = true;
+                        tiles.extend((0..width).map(|k| Pos::new(b.x + k, b.y) + d));
                     }
                 }
             }
-            Cell::Wall => (),
+            if !blocked {
+                for (b, _) in boxes.iter_mut().zip(&pushed).filter(|(_, p)| **p) {
+                    *b += d;
+                }
+                robot += d;
+            }
         }
-    });
-    grid.iter().enumerate().fold(0, |acc, (i, r)| {
-        acc + r.iter().enumerate().fold(0, |acci, (j, c)| match c {
-            Cell::Box(true) => acci + 100 * i + j,
-            _ => acci,
-        })
-    })
+        boxes.iter().map(|b| 100 * b.y + b.x).sum()
+    }
+
+    impl Reference for Day15 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            warehouse(input, 1).into()
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            warehouse(input, 2).into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -265,10 +361,45 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 10092)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(10092))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 9021)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(9021))
+    }
+
+    #[test]
+    fn frames() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(Day15::frames(&input, &(), 1).count(), input.moves.len() + 1);
+        assert_eq!(
+            Day15::frames(&input, &(), 1).last().unwrap().to_string(),
+            "##########
+#.O.O.OOO#
+#........#
+#OO......#
+#OO@.....#
+#O#.....O#
+#O.....OO#
+#O.....OO#
+#OO....OO#
+##########
+"
+        );
+        assert_eq!(
+            Day15::frames(&input, &(), 2).last().unwrap().to_string(),
+            "####################
+##[].......[].[][]##
+##[]...........[].##
+##[]........[][][]##
+##[]......[]....[]##
+##..##......[]....##
+##..[]............##
+##..@......[].[][]##
+##......[][]..[]..##
+####################
+"
+        );
     }
 }
@@ -1,6 +1,14 @@
-use std::collections::{BinaryHeap, HashSet};
+use rustc_hash::FxHashSet;
 
-use rustc_hash::{FxHashMap, FxHashSet};
+use crate::answer::Answer;
+use crate::direction::Direction;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::pos::Pos;
+use crate::search::{self, Paths};
+use crate::solution::Solution;
+use crate::trace::trace;
+use crate::viz::{Animate, Frame, Glyph, Rgb};
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
 pub enum Cell {
@@ -8,187 +16,223 @@ pub enum Cell {
     Wall,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Hash)]
-pub enum Dir {
-    North,
-    South,
-    West,
-    East,
+#[derive(Debug, Clone)]
+pub struct Input {
+    grid: Grid<Cell>,
+    start: Pos,
+    end: Pos,
 }
 
-impl Dir {
-    fn rotate_clockwise(&self) -> Dir {
-        match self {
-            Dir::North => Dir::East,
-            Dir::South => Dir::West,
-            Dir::West => Dir::North,
-            Dir::East => Dir::South,
-        }
-    }
-
-    fn rotate_counterclockwise(&self) -> Dir {
-        match self {
-            Dir::North => Dir::West,
-            Dir::South => Dir::East,
-            Dir::West => Dir::South,
-            Dir::East => Dir::North,
+#[aoc_generator(day16)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(16, input);
+    let mut start = None;
+    let mut end = None;
+    let grid = src.grid(input, "one of #.SE", |p, c| match c {
+        '#' => Some(Cell::Wall),
+        '.' => Some(Cell::Empty),
+        'S' => {
+            start = Some(p);
+            Some(Cell::Empty)
         }
-    }
-
-    fn get_delta(&self) -> (isize, isize) {
-        match self {
-            Dir::North => (-1, 0),
-            Dir::South => (1, 0),
-            Dir::West => (0, -1),
-            Dir::East => (0, 1),
+        'E' => {
+            end = Some(p);
+            Some(Cell::Empty)
         }
-    }
+        _ => None,
+    })?;
+    Ok(Input {
+        grid,
+        start: start.ok_or_else(|| src.eof("a start tile"))?,
+        end: end.ok_or_else(|| src.eof("an end tile"))?,
+    })
 }
+type State = (Pos, Direction);
 
-#[derive(Debug, Clone)]
-pub struct Input {
-    grid: Vec<Vec<Cell>>,
-    start: (usize, usize),
-    end: (usize, usize),
+/// Scores of the reindeer states, starting east on the start tile.
+fn scores(input: &Input) -> Paths<State> {
+    search::dijkstra([(input.start, Direction::Right)], |&(p, d)| {
+        // Neighbours are asked for once per state popped with its best score.
+        trace!(tile = %p, facing = ?d, "heap pop");
+        let forward = input
+            .grid
+            .step(p, d.delta())
+            .filter(|&next| input.grid[next] == Cell::Empty)
+            .map(|next| ((next, d), 1));
+        forward.into_iter().chain([
+            ((p, d.clockwise()), 1000),
+            ((p, d.counter_clockwise()), 1000),
+        ])
+    })
 }
 
-#[aoc_generator(day16)]
-pub fn parse(input: &str) -> Input {
-    let mut start = (0, 0);
-    let mut end = (0, 0);
-    Input {
-        grid: input
-            .lines()
-            .enumerate()
-            .map(|(i, c)| {
-                c.chars()
-                    .enumerate()
-                    .map(|(j, c)| match c {
-                        '#' => Cell::Wall,
-                        '.' => Cell::Empty,
-                        'S' => {
-                            start = (i, j);
-                            Cell::Empty
-                        }
-                        'E' => {
-                            end = (i, j);
-                            Cell::Empty
-                        }
-                        _ => unreachable!(),
-                    })
-                    .collect()
-            })
-            .collect(),
-        start,
-        end,
-    }
+/// Facing directions on the end tile reached with the best score, `None` if
+/// the end cannot be reached.
+fn best_ends(input: &Input, scores: &Paths<State>) -> Option<(usize, Vec<State>)> {
+    let ends = Direction::ALL
+        .iter()
+        .filter_map(|&d| Some(((input.end, d), scores.distance(&(input.end, d))?)));
+    let best = ends.clone().map(|(_, s)| s).min()?;
+    Some((
+        best,
+        ends.filter(|&(_, s)| s == best).map(|(e, _)| e).collect(),
+    ))
 }
+
+/// Lowest score, 0 if the end cannot be reached.
 #[aoc(day16, part1)]
-pub fn part1(input: &Input) -> usize {
-    let mut heap = BinaryHeap::new();
-    let mut visited = FxHashMap::default();
-    heap.push((0, input.start, Dir::East));
-
-    while let Some((s, p, d)) = heap.pop() {
-        let dp = d.get_delta();
-        let next = (p.0.wrapping_add_signed(dp.0), p.1.wrapping_add_signed(dp.1));
-        if input.end == p {
-            return -s as usize;
-        }
+pub fn part1(input: &Input) -> Answer {
+    best_ends(input, &scores(input))
+        .map_or(0, |(best, _)| best)
+        .into()
+}
 
-        if let Some(v) = visited.get(&(p, d)) {
-            if *v > s {
-                continue;
-            }
-        }
-        visited.insert((p, d), s);
+/// Tiles on a best path, 0 if the end cannot be reached.
+#[aoc(day16, part2)]
+pub fn part2(input: &Input) -> Answer {
+    let scores = scores(input);
+    let Some((_, ends)) = best_ends(input, &scores) else {
+        return 0.into();
+    };
+    let tiles: FxHashSet<_> = scores.on_paths(ends).into_iter().map(|(p, _)| p).collect();
+    tiles.len().into()
+}
 
-        match input.grid[next.0][next.1] {
-            Cell::Empty => heap.push((s - 1, next, d)),
-            Cell::Wall => (),
-        }
+pub struct Day16;
 
-        if let Some(v) = visited.get(&(p, d.rotate_clockwise())) {
-            if *v < s - 1000 {
-                heap.push((s - 1000, p, d.rotate_clockwise()));
-            }
-        } else {
-            heap.push((s - 1000, p, d.rotate_clockwise()));
-        }
+impl Solution for Day16 {
+    const DAY: u8 = 16;
 
-        if let Some(v) = visited.get(&(p, d.rotate_counterclockwise())) {
-            if *v < s - 1000 {
-                heap.push((s - 1000, p, d.rotate_counterclockwise()));
-            }
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+/// The reindeer walking one of the best paths, part 2 revealing the tiles of
+/// every best path by increasing score.
+impl Animate for Day16 {
+    fn frames<'a>(input: &'a Input, _: &'a (), part: u8) -> Box<dyn Iterator<Item = Frame> + 'a> {
+        let scores = scores(input);
+        let ends = best_ends(input, &scores).map_or_else(Vec::new, |(_, ends)| ends);
+        let states = if part == 1 {
+            ends.first()
+                .and_then(|end| scores.path(end))
+                .unwrap_or_default()
         } else {
-            heap.push((s - 1000, p, d.rotate_counterclockwise()));
-        }
+            let mut states: Vec<State> = scores.on_paths(ends).into_iter().collect();
+            states.sort_by_key(|s| (scores.distance(s), *s));
+            states
+        };
+        let maze = Frame::new(&input.grid, |c| match c {
+            Cell::Wall => Glyph::new('#', Rgb::GREY),
+            Cell::Empty => Glyph::new('.', Rgb::DARK),
+        })
+        .path([input.start], Glyph::new('S', Rgb::BLUE))
+        .path([input.end], Glyph::new('E', Rgb::BLUE));
+        Box::new((1..=states.len()).map(move |n| {
+            let (p, d) = states[n - 1];
+            maze.clone()
+                .path(
+                    states[..n - 1].iter().map(|&(p, _)| p),
+                    Glyph::new('O', Rgb::YELLOW),
+                )
+                .path([p], Glyph::new(d.arrow(), Rgb::RED))
+        }))
     }
-    unreachable!()
 }
-#[aoc(day16, part2)]
-pub fn part2(input: &Input) -> usize {
-    let mut heap = BinaryHeap::new();
-    let mut visited = FxHashMap::default();
-    let mut res = FxHashSet::default();
-    heap.push((0, input.start, Dir::East, vec![input.start]));
-
-    let mut score = 0;
-
-    while let Some((s, p, d, prev)) = heap.pop() {
-        if input.end == p {
-            if score == 0 {
-                score = s;
-            }
-            if s == score {
-                for p in prev {
-                    res.insert(p);
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use itertools::Itertools;
+    use rustc_hash::FxHashMap;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Lowest score of every state reachable from `from`, relaxing `moves`
+    /// over and over until nothing improves.
+    fn relax(
+        from: &[State],
+        moves: impl Fn(State) -> Vec<(State, usize)>,
+    ) -> FxHashMap<State, usize> {
+        let mut best: FxHashMap<State, usize> = from.iter().map(|&s| (s, 0)).collect();
+        loop {
+            let mut improved = false;
+            for (s, score) in best.clone() {
+                for (next, cost) in moves(s) {
+                    if best.get(&next).is_none_or(|&b| score + cost < b) {
+                        best.insert(next, score + cost);
+                        improved = true;
+                    }
                 }
-                continue;
             }
-            break;
-        }
-
-        if let Some(v) = visited.get(&(p, d)) {
-            if *v > s {
-                continue;
+            if !improved {
+                return best;
             }
         }
-        visited.insert((p, d), s);
-
-        let dp = d.get_delta();
-        let next = (p.0.wrapping_add_signed(dp.0), p.1.wrapping_add_signed(dp.1));
-        match input.grid[next.0][next.1] {
-            Cell::Empty => {
-                let mut prev = prev.clone();
-                prev.push(next);
-                heap.push((s - 1, next, d, prev));
-            }
-            Cell::Wall => (),
+    }
+
+    /// States one move away, walking `dir` times the facing direction.
+    fn moves(input: &Input, (p, d): State, dir: isize) -> Vec<(State, usize)> {
+        let mut moves = vec![
+            ((p, d.clockwise()), 1000),
+            ((p, d.counter_clockwise()), 1000),
+        ];
+        if let Some(next) = input.grid.step(p, d.delta() * dir)
+            && input.grid[next] == Cell::Empty
+        {
+            moves.push(((next, d), 1));
         }
+        moves
+    }
 
-        if let Some(v) = visited.get(&(p, d.rotate_clockwise())) {
-            if *v <= s - 1000 {
-                heap.push((s - 1000, p, d.rotate_clockwise(), prev.clone()));
-            }
-        } else {
-            heap.push((s - 1000, p, d.rotate_clockwise(), prev.clone()));
+    fn best(input: &Input, from_start: &FxHashMap<State, usize>) -> usize {
+        Direction::ALL
+            .iter()
+            .filter_map(|&d| from_start.get(&(input.end, d)).copied())
+            .min()
+            .unwrap_or(0)
+    }
+
+    impl Reference for Day16 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let from_start = relax(&[(input.start, Direction::Right)], |s| moves(input, s, 1));
+            best(input, &from_start).into()
         }
 
-        if let Some(v) = visited.get(&(p, d.rotate_counterclockwise())) {
-            if *v <= s - 1000 {
-                heap.push((s - 1000, p, d.rotate_counterclockwise(), prev.clone()));
-            }
-        } else {
-            heap.push((s - 1000, p, d.rotate_counterclockwise(), prev.clone()));
+        /// Tiles of a state whose scores from the start and to the end add
+        /// up to the best one.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let from_start = relax(&[(input.start, Direction::Right)], |s| moves(input, s, 1));
+            let ends = Direction::ALL.map(|d| (input.end, d));
+            let to_end = relax(&ends, |s| moves(input, s, -1));
+            let best = best(input, &from_start);
+            from_start
+                .iter()
+                .filter(|&(s, score)| to_end.get(s).is_some_and(|to| score + to == best))
+                .map(|((p, _), _)| p)
+                .unique()
+                .count()
+                .into()
         }
     }
-
-    res.len()
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reference::Reference;
     const INPUT: &str = "###############
 #.......#....E#
 #.#.###.#.###.#
@@ -225,12 +269,23 @@ mod tests {
 
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 7036);
-        assert_eq!(part1(&parse(INPUT2)), 11048);
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(7036));
+        assert_eq!(part1(&parse(INPUT2).unwrap()), Answer::Int(11048));
     }
+
+    #[test]
+    fn unreachable() {
+        let input = parse("#######\n#S.#.E#\n#######").unwrap();
+        assert_eq!(part1(&input), Answer::Int(0));
+        assert_eq!(part2(&input), Answer::Int(0));
+        assert_eq!(<Day16 as Reference>::part1(&input, &()), Answer::Int(0));
+        assert_eq!(<Day16 as Reference>::part2(&input, &()), Answer::Int(0));
+        assert_eq!(Day16::frames(&input, &(), 1).count(), 0);
+    }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 45);
-        assert_eq!(part2(&parse(INPUT2)), 64);
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(45));
+        assert_eq!(part2(&parse(INPUT2).unwrap()), Answer::Int(64));
     }
 }
@@ -1,5 +1,15 @@
+use itertools::Itertools;
+use nom::combinator::consumed;
+use nom::sequence::{separated_pair, terminated, tuple};
 use std::{collections::VecDeque, ops::BitXor};
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{blank_line, commas, eol, field, number};
+use crate::solution::Solution;
+use crate::trace::trace;
+
+#[derive(Debug)]
 pub enum Inst {
     Adv(usize), // A / 2^combo -> A (truncatd)
     Bxl(usize), // B xor literal
@@ -10,74 +20,83 @@ pub enum Inst {
     Bdv(usize), // B / 2^combo -> A (truncated)
     Cdv(usize), // C / 2^combo -> A (truncated)
 }
+#[derive(Debug)]
 pub struct Input {
     a: usize,
-    _b: usize,
-    _c: usize,
+    b: usize,
+    c: usize,
 
     inst: Vec<Inst>,
     input: Vec<usize>,
 }
+
+impl Input {
+    /// Initial values of registers A, B and C.
+    pub fn registers(&self) -> (usize, usize, usize) {
+        (self.a, self.b, self.c)
+    }
+
+    /// Program as the numbers it was written with.
+    pub fn program(&self) -> &[usize] {
+        &self.input
+    }
+
+    /// Output of the program started with `a` in register A, and B and C at
+    /// their initial values.
+    pub fn run(&self, a: usize) -> Vec<usize> {
+        run(&self.inst, a, self.b, self.c)
+    }
+}
+
 #[aoc_generator(day17)]
-pub fn parse(input: &str) -> Input {
-    let (regs, instructions) = input.split_once("\n\n").unwrap();
-    let mut reg_it = regs.lines();
-
-    let int_inst: Vec<usize> = instructions
-        .lines()
-        .next()
-        .unwrap()
-        .strip_prefix("Program: ")
-        .unwrap()
-        .split(",")
-        .map(|i| i.parse().unwrap())
-        .collect();
-
-    let mut i = 0;
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(17, input);
+    let register = |name| field(name, number("a register value"));
+    let registers = tuple((
+        terminated(register("Register A:"), eol),
+        terminated(register("Register B:"), eol),
+        register("Register C:"),
+    ));
+    let program = field(
+        "Program:",
+        commas(consumed(number::<usize>("a 3-bit number"))),
+    );
+    let ((a, b, c), program) = src.run(separated_pair(registers, blank_line, program))?;
+    let (codes, int_inst): (Vec<&str>, Vec<usize>) = program.into_iter().unzip();
+    let last = codes[codes.len() - 1];
+
     let mut inst = Vec::new();
-    loop {
-        if i >= int_inst.len() {
-            break;
+    for (i, pair) in int_inst.chunks(2).enumerate() {
+        let &[op, operand] = pair else {
+            return Err(src.error(&last[last.len()..], "an operand"));
+        };
+        if operand > 7 {
+            return Err(src.error(codes[2 * i + 1], "a 3-bit operand"));
         }
-        match int_inst[i] {
-            0 => inst.push(Inst::Adv(int_inst[i + 1])),
-            1 => inst.push(Inst::Bxl(int_inst[i + 1])),
-            2 => inst.push(Inst::Bst(int_inst[i + 1])),
-            3 => inst.push(Inst::Jnz(int_inst[i + 1])),
-            4 => inst.push(Inst::Bxc(int_inst[i + 1])),
-            5 => inst.push(Inst::Out(int_inst[i + 1])),
-            6 => inst.push(Inst::Bdv(int_inst[i + 1])),
-            7 => inst.push(Inst::Cdv(int_inst[i + 1])),
-            _ => unreachable!(),
+        // Combo operand 7 is reserved.
+        if operand == 7 && matches!(op, 0 | 2 | 5 | 6 | 7) {
+            return Err(src.error(codes[2 * i + 1], "a combo operand between 0 and 6"));
         }
-        i += 2;
+        inst.push(match op {
+            0 => Inst::Adv(operand),
+            1 => Inst::Bxl(operand),
+            2 => Inst::Bst(operand),
+            3 => Inst::Jnz(operand),
+            4 => Inst::Bxc(operand),
+            5 => Inst::Out(operand),
+            6 => Inst::Bdv(operand),
+            7 => Inst::Cdv(operand),
+            _ => return Err(src.error(codes[2 * i], "an opcode between 0 and 7")),
+        });
     }
 
-    Input {
-        a: reg_it
-            .next()
-            .unwrap()
-            .strip_prefix("Register A: ")
-            .unwrap()
-            .parse()
-            .unwrap(),
-        _b: reg_it
-            .next()
-            .unwrap()
-            .strip_prefix("Register B: ")
-            .unwrap()
-            .parse()
-            .unwrap(),
-        _c: reg_it
-            .next()
-            .unwrap()
-            .strip_prefix("Register C: ")
-            .unwrap()
-            .parse()
-            .unwrap(),
+    Ok(Input {
+        a,
+        b,
+        c,
         inst,
         input: int_inst,
-    }
+    })
 }
 
 fn get_combo(combo: usize, a: usize, b: usize, c: usize) -> usize {
@@ -90,17 +109,25 @@ fn get_combo(combo: usize, a: usize, b: usize, c: usize) -> usize {
     }
 }
 
-fn run(insts: &Vec<Inst>, a: usize) -> Vec<usize> {
-    let (mut a, mut b, mut c) = (a, 0, 0);
+/// `a` shifted right by `n` bits, 0 once every bit is shifted out.
+fn shr(a: usize, n: usize) -> usize {
+    u32::try_from(n)
+        .ok()
+        .and_then(|n| a.checked_shr(n))
+        .unwrap_or(0)
+}
+
+fn run(insts: &[Inst], mut a: usize, mut b: usize, mut c: usize) -> Vec<usize> {
     let mut pc = 0;
     let mut res = Vec::new();
     loop {
         if pc >= insts.len() {
             break;
         }
+        trace!(pc, inst = ?insts[pc], a, b, c, "execute");
         match insts[pc] {
             Inst::Adv(combo) => {
-                a = a / 2usize.pow(get_combo(combo, a, b, c) as u32);
+                a = shr(a, get_combo(combo, a, b, c));
                 pc += 1;
             }
             Inst::Bxl(lit) => {
@@ -112,10 +139,14 @@ fn run(insts: &Vec<Inst>, a: usize) -> Vec<usize> {
                 pc += 1;
             }
             Inst::Jnz(lit) => {
-                if a != 0 {
-                    pc = lit;
-                } else {
+                if a == 0 {
                     pc += 1;
+                } else if lit % 2 == 0 {
+                    // `lit` counts numbers, two per instruction.
+                    pc = lit / 2;
+                } else {
+                    // Out of step with the instructions.
+                    break;
                 }
             }
             Inst::Bxc(_) => {
@@ -127,11 +158,11 @@ fn run(insts: &Vec<Inst>, a: usize) -> Vec<usize> {
                 pc += 1;
             }
             Inst::Bdv(combo) => {
-                b = a / 2usize.pow(get_combo(combo, a, b, c) as u32);
+                b = shr(a, get_combo(combo, a, b, c));
                 pc += 1;
             }
             Inst::Cdv(combo) => {
-                c = a / 2usize.pow(get_combo(combo, a, b, c) as u32);
+                c = shr(a, get_combo(combo, a, b, c));
                 pc += 1;
             }
         }
@@ -140,22 +171,21 @@ fn run(insts: &Vec<Inst>, a: usize) -> Vec<usize> {
 }
 
 #[aoc(day17, part1)]
-pub fn part1(input: &Input) -> usize {
-    run(&input.inst, input.a)
+pub fn part1(input: &Input) -> Answer {
+    run(&input.inst, input.a, input.b, input.c)
         .iter()
-        .rev()
-        .enumerate()
-        .fold(0, |acc, (i, r)| acc + 10usize.pow(i as u32) * r)
+        .join(",")
+        .into()
 }
 #[aoc(day17, part2)]
-pub fn part2(input: &Input) -> usize {
+pub fn part2(input: &Input) -> Answer {
     let mut tests = VecDeque::new();
     tests.push_front((0, input.input.len() - 1));
 
     while let Some((a, shift)) = tests.pop_front() {
         for v in 0..8 {
             let new_a = (a << 3) + v;
-            let res = run(&input.inst, new_a);
+            let res = run(&input.inst, new_a, input.b, input.c);
             let common = input
                 .input
                 .iter()
@@ -165,13 +195,98 @@ pub fn part2(input: &Input) -> usize {
 
             if common {
                 if shift == 0 {
-                    return new_a;
+                    return new_a.into();
                 }
                 tests.push_back((new_a, shift - 1));
             }
         }
     }
-    unreachable!()
+    // The program never prints itself.
+    0.into()
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Output of the program read straight from its numbers.
+    fn execute(program: &[usize], mut a: usize, mut b: usize, mut c: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        let mut pc = 0;
+        while pc + 1 < program.len() {
+            let literal = program[pc + 1];
+            let combo = |a, b, c| match literal {
+                0..=3 => literal,
+                4 => a,
+                5 => b,
+                6 => c,
+                _ => unreachable!("combo operand 7"),
+            };
+            let shifted = |a: usize, n: usize| {
+                u32::try_from(n)
+                    .ok()
+                    .and_then(|n| a.checked_shr(n))
+                    .unwrap_or(0)
+            };
+            match program[pc] {
+                0 => a = shifted(a, combo(a, b, c)),
+                1 => b ^= literal,
+                2 => b = combo(a, b, c) % 8,
+                3 if a != 0 && literal % 2 == 1 => break,
+                3 if a != 0 => {
+                    pc = literal;
+                    continue;
+                }
+                3 => (),
+                4 => b ^= c,
+                5 => out.push(combo(a, b, c) % 8),
+                6 => b = shifted(a, combo(a, b, c)),
+                _ => c = shifted(a, combo(a, b, c)),
+            }
+            pc += 2;
+        }
+        out
+    }
+
+    impl Reference for Day17 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            execute(&input.input, input.a, input.b, input.c)
+                .iter()
+                .join(",")
+                .into()
+        }
+
+        /// Tries every value of register A from 0, only practical on programs
+        /// printing a few numbers.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            (0..)
+                .find(|&a| execute(&input.input, a, input.b, input.c) == input.input)
+                .unwrap()
+                .into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +299,63 @@ Register C: 0
 Program: 0,1,5,4,3,0";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 4635635210)
+        assert_eq!(
+            part1(&parse(INPUT).unwrap()),
+            Answer::from("4,6,3,5,6,3,5,2,1,0")
+        )
+    }
+
+    #[test]
+    fn emulator() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(input.registers(), (729, 0, 0));
+        assert_eq!(input.program(), [0, 1, 5, 4, 3, 0]);
+        assert_eq!(input.run(729), [4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+        assert_eq!(input.run(0), [0]);
+
+        let input =
+            parse("Register A: 0\nRegister B: 13\nRegister C: 3\n\nProgram: 5,5,5,6").unwrap();
+        assert_eq!(input.run(0), [5, 3]);
+        assert_eq!(part1(&input), Answer::from("5,3"));
+    }
+
+    #[test]
+    fn jumps() {
+        let program = |b, program| {
+            parse(&format!(
+                "Register A: 0\nRegister B: {b}\nRegister C: 0\n\nProgram: {program}"
+            ))
+            .unwrap()
+        };
+        // Back to the second instruction, at number 2.
+        assert_eq!(program(0, "5,5,0,1,5,4,3,2").run(4), [0, 2, 1, 0]);
+        // An odd target halts.
+        assert!(program(0, "0,1,3,1,5,4").run(4).is_empty());
+        assert_eq!(program(0, "0,1,3,1,5,4").run(1), [0]);
+        // Shifting by 64 bits or more clears the register.
+        assert_eq!(program(0, "0,4,5,4").run(100), [0]);
+        assert_eq!(program(1usize << 32, "0,5,5,4").run(13), [0]);
+    }
+
+    #[test]
+    fn bad_input() {
+        let err =
+            parse("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 1,7,0,7").unwrap_err();
+        assert_eq!((err.line, err.column), (5, 16));
+        assert_eq!(err.expected, "a combo operand between 0 and 6");
+        let err = parse("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 0,8").unwrap_err();
+        assert_eq!((err.line, err.column), (5, 12));
+    }
+
+    #[test]
+    fn example2() {
+        let input = parse("Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0");
+        assert_eq!(part2(&input.unwrap()), Answer::Int(117440))
+    }
+
+    #[test]
+    fn no_quine() {
+        let input = parse("Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 1,1,5,5");
+        assert_eq!(part2(&input.unwrap()), Answer::Int(0))
     }
 }
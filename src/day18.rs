@@ -1,83 +1,224 @@
-use rustc_hash::FxHashSet;
-use std::collections::VecDeque;
+use nom::combinator::map;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::parsing::{lines, xy};
+use crate::pos::Pos;
+use crate::search;
+use crate::solution::{Solution, params};
+use crate::viz::{Animate, Frame, Glyph, Rgb};
+
+params! {
+    pub struct Params {
+        /// Side of the memory space.
+        pub size: usize = 71,
+        /// Bytes fallen in part 1.
+        pub bytes: usize = 1024,
+    }
+}
 
 pub struct Input {
-    bytes: Vec<(usize, usize)>,
-    h: usize,
-    w: usize,
+    bytes: Vec<Pos>,
 }
+
 #[aoc_generator(day18)]
-pub fn parse(input: &str) -> Input {
-    let mut h = 0;
-    let mut w = 0;
-
-    let bytes = input
-        .lines()
-        .map(|l| {
-            let (x, y) = l.split_once(",").unwrap();
-            let (x, y) = (x.parse().unwrap(), y.parse().unwrap());
-            w = std::cmp::max(w, x);
-            h = std::cmp::max(h, y);
-            (x, y)
-        })
-        .collect();
-    Input {
-        bytes,
-        w: w + 1,
-        h: h + 1,
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let byte = map(xy("a coordinate"), |(x, y)| Pos::new(x, y));
+    Ok(Input {
+        bytes: Source::new(18, input).run(lines(byte))?,
+    })
 }
 
-#[aoc(day18, part1)]
-pub fn part1(input: &Input) -> usize {
-    let mut grid = vec![vec![false; input.w]; input.h];
-    (0..1024).map(|i| input.bytes[i]).for_each(|(x, y)| {
-        grid[x][y] = true;
+fn fallen(input: &Input, size: usize, n: usize) -> Grid<bool> {
+    let mut grid = Grid::new(size, size, false);
+    input.bytes.iter().take(n).for_each(|&p| {
+        grid[p] = true;
     });
-    shortest(input, &grid).unwrap()
+    grid
+}
+
+#[aoc(day18, part1)]
+pub fn part1(input: &Input) -> Answer {
+    Day18::part1(input, &Params::default())
 }
 
 #[aoc(day18, part2)]
-pub fn part2(input: &Input) -> String {
-    let mut grid = vec![vec![false; input.w]; input.h];
-    for (x, y) in input.bytes.iter() {
-        grid[*x][*y] = true;
-        if shortest(input, &grid).is_none() {
-            return format!("{x},{y}");
+pub fn part2(input: &Input) -> Answer {
+    Day18::part2(input, &Params::default())
+}
+
+/// Number of fallen bytes once the exit is cut off, found by bisection,
+/// `None` if it never is.
+fn blocking(input: &Input, size: usize) -> Option<usize> {
+    let blocked = |n| shortest(&fallen(input, size, n)).is_none();
+    let (mut lo, mut hi) = (0, input.bytes.len());
+    if !blocked(hi) {
+        return None;
+    }
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if blocked(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
         }
     }
-    unreachable!()
+    Some(hi)
 }
 
-fn shortest(input: &Input, grid: &Vec<Vec<bool>>) -> Option<usize> {
-    let mut queue = VecDeque::new();
-    let mut visisted = FxHashSet::default();
-    queue.push_back((0, (0usize, 0usize)));
-    visisted.insert((0, 0));
+pub struct Day18;
 
-    while let Some((s, p)) = queue.pop_front() {
-        if p == (input.w - 1, input.h - 1) {
-            return Some(s);
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    /// 0 if the exit is already cut off.
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        shortest(&fallen(input, params.size, params.bytes))
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// 0 if the exit is never cut off.
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        match blocking(input, params.size) {
+            Some(n) => input.bytes[n - 1].to_string().into(),
+            None => 0.into(),
         }
+    }
 
-        for d in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-            let np = (p.0.wrapping_add_signed(d.0), p.1.wrapping_add_signed(d.1));
-            if np.0 >= input.w || np.1 >= input.h || grid[np.0][np.1] {
-                continue;
+    fn check(input: &Self::Parsed, params: &Params) -> Result<(), String> {
+        if params.size == 0 {
+            return Err("size is 0, expected at least 1".to_owned());
+        }
+        if params.bytes > input.bytes.len() {
+            return Err(format!(
+                "bytes is {}, but only {} bytes fall",
+                params.bytes,
+                input.bytes.len()
+            ));
+        }
+        if let Some(p) = input.bytes.iter().find(|p| p.x.max(p.y) >= params.size) {
+            return Err(format!(
+                "byte {p} falls outside of a memory space of size {}",
+                params.size
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn shortest(grid: &Grid<bool>) -> Option<usize> {
+    escape(grid).map(|path| path.len() - 1)
+}
+
+/// One of the shortest paths to the exit, both corners included.
+fn escape(grid: &Grid<bool>) -> Option<Vec<Pos>> {
+    let end = Pos::new(grid.width() - 1, grid.height() - 1);
+    search::astar(
+        Pos::default(),
+        |&p| {
+            grid.neighbours4(p)
+                .filter(|&np| !grid[np])
+                .map(|np| (np, 1))
+        },
+        |p| p.manhattan(end),
+        |&p| p == end,
+    )
+    .map(|(_, path)| path)
+}
+
+/// Bytes falling one by one with the way out, until the bytes of part 1 have
+/// fallen or, for part 2, until the exit is cut off.
+impl Animate for Day18 {
+    fn frames<'a>(
+        input: &'a Input,
+        params: &'a Params,
+        part: u8,
+    ) -> Box<dyn Iterator<Item = Frame> + 'a> {
+        let end = if part == 1 {
+            params.bytes.min(input.bytes.len())
+        } else {
+            blocking(input, params.size).unwrap_or(input.bytes.len())
+        };
+        let mut path = escape(&fallen(input, params.size, 0));
+        Box::new((0..=end).map(move |n| {
+            let grid = fallen(input, params.size, n);
+            let byte = n.checked_sub(1).map(|i| input.bytes[i]);
+            // The way out only changes when a byte falls on it.
+            if path
+                .as_ref()
+                .is_some_and(|path| byte.is_some_and(|b| path.contains(&b)))
+            {
+                path = escape(&grid);
             }
-            if !visisted.contains(&np) {
-                visisted.insert(np);
-                queue.push_back((s + 1, np));
+            Frame::new(&grid, |&byte| {
+                if byte {
+                    Glyph::new('#', Rgb::GREY)
+                } else {
+                    Glyph::new('.', Rgb::DARK)
+                }
+            })
+            .path(path.iter().flatten().copied(), Glyph::new('O', Rgb::GREEN))
+            .highlight(byte, Rgb::RED)
+        }))
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Steps to the exit found by a breadth first search.
+    fn steps(grid: &Grid<bool>) -> Option<usize> {
+        let end = Pos::new(grid.width() - 1, grid.height() - 1);
+        let mut distance = Grid::new(grid.width(), grid.height(), None);
+        distance[Pos::default()] = Some(0);
+        let mut queue = VecDeque::from([Pos::default()]);
+        while let Some(p) = queue.pop_front() {
+            let d = distance[p]?;
+            for np in grid.neighbours4(p) {
+                if !grid[np] && distance[np].is_none() {
+                    distance[np] = Some(d + 1);
+                    queue.push_back(np);
+                }
             }
         }
+        distance[end]
     }
 
-    None
+    impl Reference for Day18 {
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            steps(&fallen(input, params.size, params.bytes))
+                .unwrap_or(0)
+                .into()
+        }
+
+        /// Lets the bytes fall one by one until the exit is cut off.
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            (1..=input.bytes.len())
+                .find(|&n| steps(&fallen(input, params.size, n)).is_none())
+                .map_or(0.into(), |n| input.bytes[n - 1].to_string().into())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
     const INPUT: &str = "5,4
 4,2
 4,5
@@ -105,10 +246,61 @@ mod tests {
 2,0";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 0)
+        let params = Params { size: 7, bytes: 12 };
+        assert_eq!(
+            <Day18 as Solution>::part1(&parse(INPUT).unwrap(), &params),
+            Answer::Int(22)
+        )
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 0)
+        let params = Params { size: 7, bytes: 12 };
+        assert_eq!(
+            <Day18 as Solution>::part2(&parse(INPUT).unwrap(), &params),
+            Answer::from("6,1")
+        )
+    }
+
+    #[test]
+    fn exit() {
+        let input = parse("1,0\n0,1").unwrap();
+        let params = Params { size: 3, bytes: 2 };
+        assert_eq!(<Day18 as Solution>::part1(&input, &params), Answer::Int(0));
+        assert_eq!(<Day18 as Reference>::part1(&input, &params), Answer::Int(0));
+        assert_eq!(
+            <Day18 as Solution>::part2(&input, &params),
+            Answer::from("0,1")
+        );
+        assert_eq!(
+            <Day18 as Reference>::part2(&input, &params),
+            Answer::from("0,1")
+        );
+
+        let input = parse("1,1").unwrap();
+        let params = Params { size: 3, bytes: 1 };
+        assert_eq!(<Day18 as Solution>::part2(&input, &params), Answer::Int(0));
+        assert_eq!(<Day18 as Reference>::part2(&input, &params), Answer::Int(0));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn naive_search(seed: u64, size in 2usize..16, safe in 0usize..64) {
+            let input = parse(&generate::day18(&mut rng(seed), size, safe)).unwrap();
+            // Only the bytes off the protected staircase can fall first.
+            let safe = safe.min((size - 1) * (size - 1));
+            prop_assert!(shortest(&fallen(&input, size, safe)).is_some());
+            let params = Params { size, bytes: safe };
+            prop_assert_eq!(
+                <Day18 as Solution>::part1(&input, &params),
+                <Day18 as Reference>::part1(&input, &params)
+            );
+            prop_assert_eq!(
+                <Day18 as Solution>::part2(&input, &params),
+                <Day18 as Reference>::part2(&input, &params)
+            );
+        }
     }
 }
@@ -1,5 +1,13 @@
+use nom::combinator::map;
+use nom::sequence::separated_pair;
 use rustc_hash::{FxHashMap, FxHashSet};
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::par::*;
+use crate::parsing::{blank_line, commas, lines, lowercase};
+use crate::solution::Solution;
+
 #[derive(Clone, Debug)]
 pub struct Input {
     stripes: FxHashSet<String>,
@@ -8,21 +16,15 @@ pub struct Input {
 }
 
 #[aoc_generator(day19)]
-pub fn parse(input: &str) -> Input {
-    let (f, s) = input.split_once("\n\n").unwrap();
-    let mut max = 0;
-    let stripes = f
-        .split(", ")
-        .map(|s| {
-            max = std::cmp::max(max, s.len());
-            s.to_owned()
-        })
-        .collect();
-    Input {
-        max_stripe_len: max,
-        stripes,
-        patterns: s.lines().map(|s| s.to_owned()).collect(),
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let towel = || map(lowercase("a stripe colour"), str::to_owned);
+    let (stripes, patterns) =
+        Source::new(19, input).run(separated_pair(commas(towel()), blank_line, lines(towel())))?;
+    Ok(Input {
+        max_stripe_len: stripes.iter().map(String::len).max().unwrap_or(0),
+        stripes: stripes.into_iter().collect(),
+        patterns,
+    })
 }
 
 fn solve(p: String, input: &Input, seen: &mut FxHashMap<String, usize>) -> usize {
@@ -48,24 +50,84 @@ fn solve(p: String, input: &Input, seen: &mut FxHashMap<String, usize>) -> usize
 }
 
 #[aoc(day19, part1)]
-pub fn part1(input: &Input) -> usize {
+pub fn part1(input: &Input) -> Answer {
     input
         .patterns
-        .iter()
+        .par_iter()
         .filter(|&p| solve(p.to_owned(), input, &mut FxHashMap::default()) > 0)
         .count()
+        .into()
 }
 
 #[aoc(day19, part2)]
-pub fn part2(input: &Input) -> usize {
-    input.patterns.iter().fold(0, |acc, p| {
-        acc + solve(p.clone().to_owned(), input, &mut FxHashMap::default())
-    })
+pub fn part2(input: &Input) -> Answer {
+    input
+        .patterns
+        .par_iter()
+        .map(|p| solve(p.clone().to_owned(), input, &mut FxHashMap::default()))
+        .sum::<usize>()
+        .into()
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Arrangements of every design, trying every towel at every position.
+    fn arrangements(input: &Input) -> impl Iterator<Item = usize> + '_ {
+        input.patterns.iter().map(|design| {
+            let mut ways = vec![0; design.len() + 1];
+            ways[0] = 1;
+            for i in 0..design.len() {
+                for t in &input.stripes {
+                    if design[i..].starts_with(t.as_str()) {
+                        ways[i + t.len()] += ways[i];
+                    }
+                }
+            }
+            ways[design.len()]
+        })
+    }
+
+    impl Reference for Day19 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            arrangements(input).filter(|&w| w > 0).count().into()
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            arrangements(input).sum::<usize>().into()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
     const INPUT: &str = "r, wr, b, g, bwu, rb, gb, br
 
 brwrr
@@ -78,10 +140,22 @@ brgr
 bbrgwb";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 6)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(6))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 16)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(16))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn naive_arrangements(seed: u64, size in 1usize..12) {
+            let input = parse(&generate::day19(&mut rng(seed), size)).unwrap();
+            prop_assert_eq!(part1(&input), <Day19 as Reference>::part1(&input, &()));
+            prop_assert_eq!(part2(&input), <Day19 as Reference>::part2(&input, &()));
+        }
     }
 }
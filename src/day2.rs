@@ -1,14 +1,16 @@
 use itertools::{FoldWhile, Itertools};
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{lines, number, spaced};
+use crate::solution::Solution;
+
 #[aoc_generator(day2)]
-pub fn parse(input: &str) -> Vec<Vec<usize>> {
-    input
-        .lines()
-        .map(|l| l.split(" ").map(|c| c.parse().unwrap()).collect())
-        .collect()
+pub fn parse(input: &str) -> Result<Vec<Vec<usize>>, ParseError> {
+    Source::new(2, input).run(lines(spaced(number("a level"))))
 }
 #[aoc(day2, part1)]
-pub fn part1(input: &[Vec<usize>]) -> usize {
+pub fn part1(input: &[Vec<usize>]) -> Answer {
     input
         .iter()
         .filter(|&l| {
@@ -28,10 +30,11 @@ pub fn part1(input: &[Vec<usize>]) -> usize {
                 .0
         })
         .count()
+        .into()
 }
 
 #[aoc(day2, part2)]
-pub fn part2(input: &[Vec<usize>]) -> usize {
+pub fn part2(input: &[Vec<usize>]) -> Answer {
     input
         .iter()
         .filter(|&l| {
@@ -62,7 +65,68 @@ pub fn part2(input: &[Vec<usize>]) -> usize {
             false
         })
         .count()
+        .into()
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Parsed = Vec<Vec<usize>>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
 }
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    fn safe(levels: &[usize]) -> bool {
+        let gradual = |a: usize, b: usize| (1..=3).contains(&a.abs_diff(b));
+        levels
+            .windows(2)
+            .all(|w| w[0] < w[1] && gradual(w[0], w[1]))
+            || levels
+                .windows(2)
+                .all(|w| w[0] > w[1] && gradual(w[0], w[1]))
+    }
+
+    impl Reference for Day2 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            input.iter().filter(|l| safe(l)).count().into()
+        }
+
+        /// Tries the report without each of its levels in turn.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            input
+                .iter()
+                .filter(|l| {
+                    safe(l)
+                        || (0..l.len()).any(|i| {
+                            let mut l = l.to_vec();
+                            l.remove(i);
+                            safe(&l)
+                        })
+                })
+                .count()
+                .into()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,10 +138,11 @@ mod tests {
 1 3 6 7 9";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 2)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(2))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 4)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(4))
     }
 }
@@ -1,6 +1,23 @@
 use itertools::Itertools;
 use rustc_hash::FxHashMap;
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::par::*;
+use crate::pos::Pos;
+use crate::search::{self, Paths};
+use crate::solution::{Solution, params};
+
+params! {
+    pub struct Params {
+        /// Picoseconds a cheat must save to be counted.
+        pub min_saving: usize = 100,
+        /// Longest cheat of part 2.
+        pub max_cheat: usize = 20,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Cell {
     Empty,
@@ -9,123 +26,244 @@ pub enum Cell {
 }
 
 pub struct Input {
-    grid: Vec<Vec<Cell>>,
-    start: (usize, usize),
-    end: (usize, usize),
+    grid: Grid<Cell>,
+    start: Pos,
 }
 
 #[aoc_generator(day20)]
-pub fn parse(input: &str) -> Input {
-    let mut start = (0, 0);
-    let mut end = (0, 0);
-    let grid = input
-        .lines()
-        .enumerate()
-        .map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(j, c)| match c {
-                    '.' => Cell::Empty,
-                    '#' => Cell::Wall,
-                    'S' => {
-                        start = (i, j);
-                        Cell::Start
-                    }
-                    'E' => {
-                        end = (i, j);
-                        Cell::Empty
-                    }
-                    _ => unreachable!(),
-                })
-                .collect()
-        })
-        .collect();
-    Input { grid, start, end }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(20, input);
+    let mut start = None;
+    let mut end = None;
+    let grid = src.grid(input, "one of .#SE", |p, c| match c {
+        '.' => Some(Cell::Empty),
+        '#' => Some(Cell::Wall),
+        'S' => {
+            start = Some(p);
+            Some(Cell::Start)
+        }
+        'E' => {
+            end = Some(p);
+            Some(Cell::Empty)
+        }
+        _ => None,
+    })?;
+    end.ok_or_else(|| src.eof("an end tile"))?;
+    Ok(Input {
+        grid,
+        start: start.ok_or_else(|| src.eof("a start tile"))?,
+    })
+}
+
+/// Distance of every track tile from the start.
+fn track(input: &Input) -> Paths<Pos> {
+    search::bfs([input.start], |&p| {
+        input
+            .grid
+            .neighbours4(p)
+            .filter(|&np| input.grid[np] != Cell::Wall)
+    })
 }
 
 #[aoc(day20, part1)]
-pub fn part1(input: &Input) -> usize {
-    let mut p = input.start;
-    let mut visited = vec![vec![0; input.grid[0].len()]; input.grid.len()];
-    for i in 0.. {
-        visited[p.0][p.1] = i;
-        if p == input.end {
-            break;
-        }
-        for dp in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-            let np = (p.0.wrapping_add_signed(dp.0), p.1.wrapping_add_signed(dp.1));
-            if input.grid[np.0][np.1] == Cell::Empty && visited[np.0][np.1] == 0 {
-                p = np;
-                break;
-            }
-        }
+pub fn part1(input: &Input) -> Answer {
+    Day20::part1(input, &Params::default())
+}
+
+/// Number of 2 picosecond cheats saving at least `min_saving`.
+fn short_cheats(input: &Input, min_saving: usize) -> usize {
+    let mut visited = Grid::new(input.grid.width(), input.grid.height(), None);
+    for (&p, d) in track(input).distances() {
+        visited[p] = Some(d);
     }
 
     let mut res = FxHashMap::default();
-    for i in 1..(input.grid.len() - 1) {
-        for j in 1..(input.grid[0].len() - 1) {
-            if input.grid[i][j] != Cell::Wall {
-                continue;
-            }
-            let mut before = ((i, j), std::usize::MAX);
-            for dp in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-                let np = (i.wrapping_add_signed(dp.0), j.wrapping_add_signed(dp.1));
-                if np.0 >= input.grid.len() || np.1 >= input.grid[0].len() {
-                    continue;
-                }
-                if visited[np.0][np.1] != 0 && visited[np.0][np.1] < before.1 {
-                    before = ((np.0, np.1), visited[np.0][np.1]);
-                }
-            }
-            let mut after = ((i, j), 0);
-            for dp in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-                let np = (i.wrapping_add_signed(dp.0), j.wrapping_add_signed(dp.1));
-                if np.0 >= input.grid.len() || np.1 >= input.grid[0].len() {
-                    continue;
-                }
-                if visited[np.0][np.1] != 0 && visited[np.0][np.1] > after.1 {
-                    after = ((np.0, np.1), visited[np.0][np.1]);
-                }
-            }
-            if after.1 == std::usize::MAX || before.1 == std::usize::MAX || after.1 == before.1 {
-                continue;
-            }
-
-            *res.entry(after.1 - before.1 - 2).or_insert(0) += 1;
+    for (p, c) in input.grid.iter() {
+        if *c != Cell::Wall {
+            continue;
         }
+        let around = input.grid.neighbours4(p).filter_map(|np| visited[np]);
+        let Some((before, after)) = around.minmax().into_option() else {
+            continue;
+        };
+        if after == before {
+            continue;
+        }
+
+        *res.entry(after - before - 2).or_insert(0) += 1;
     }
 
-    res.iter()
-        .fold(0, |acc, (k, v)| if *k >= 100 { acc + *v } else { acc })
+    res.iter().fold(
+        0,
+        |acc, (k, v)| if *k >= min_saving { acc + *v } else { acc },
+    )
 }
 
 #[aoc(day20, part2)]
-pub fn part2(input: &Input) -> usize {
-    let mut p = input.start;
-    let mut visited = FxHashMap::default();
-    for i in 0usize.. {
-        visited.insert(p, i);
-        if p == input.end {
-            break;
-        }
-        for dp in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-            let np = (p.0.wrapping_add_signed(dp.0), p.1.wrapping_add_signed(dp.1));
-            if input.grid[np.0][np.1] == Cell::Empty && !visited.contains_key(&np) {
-                p = np;
-                break;
+pub fn part2(input: &Input) -> Answer {
+    Day20::part2(input, &Params::default())
+}
+
+/// Number of cheats of up to `max_cheat` picoseconds saving at least `min_saving`.
+fn long_cheats(input: &Input, max_cheat: usize, min_saving: usize) -> usize {
+    let tiles: Vec<(Pos, usize)> = track(input).distances().map(|(&p, d)| (p, d)).collect();
+    (0..tiles.len())
+        .into_par_iter()
+        .map(|i| {
+            let (p1, d1) = tiles[i];
+            tiles[i + 1..]
+                .iter()
+                .filter(|&&(p2, d2)| {
+                    let d = p1.manhattan(p2);
+                    d <= max_cheat && d1.abs_diff(d2) >= d + min_saving
+                })
+                .count()
+        })
+        .sum()
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        short_cheats(input, params.min_saving).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        long_cheats(input, params.max_cheat, params.min_saving).into()
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Distance of every reachable tile from the start.
+    fn distances(grid: &Grid<Cell>, start: Pos) -> Grid<Option<usize>> {
+        let mut distance = Grid::new(grid.width(), grid.height(), None);
+        distance[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(p) = queue.pop_front() {
+            let d = distance[p].unwrap();
+            for np in grid.neighbours4(p) {
+                if grid[np] != Cell::Wall && distance[np].is_none() {
+                    distance[np] = Some(d + 1);
+                    queue.push_back(np);
+                }
             }
         }
+        distance
     }
 
-    visited
-        .iter()
-        .tuple_combinations()
-        .fold(0, |acc, (c1, c2)| {
-            let d = c1.0.0.abs_diff(c2.0.0) + c1.0.1.abs_diff(c2.0.1);
-            if d <= 20 && c1.1.abs_diff(*c2.1) >= d + 100 {
-                acc + 1
-            } else {
-                acc
+    impl Reference for Day20 {
+        /// Races again with each wall removed in turn.
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            // The track being a single lane, its end is the farthest tile.
+            let (end, fair) = distances(&input.grid, input.start)
+                .iter()
+                .filter_map(|(p, d)| Some((p, (*d)?)))
+                .max_by_key(|&(_, d)| d)
+                .unwrap();
+            input
+                .grid
+                .iter()
+                .filter(|&(_, c)| *c == Cell::Wall)
+                .filter(|&(p, _)| {
+                    let mut grid = input.grid.clone();
+                    grid[p] = Cell::Empty;
+                    let cheating = distances(&grid, input.start)[end].unwrap();
+                    fair - cheating >= params.min_saving
+                })
+                .count()
+                .into()
+        }
+
+        /// Tries every pair of track tiles close enough to cheat between.
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            let distance = distances(&input.grid, input.start);
+            let track: Vec<(Pos, usize)> = distance
+                .iter()
+                .filter_map(|(p, d)| Some((p, (*d)?)))
+                .collect();
+            let mut cheats = 0;
+            for &(from, before) in &track {
+                for &(to, after) in &track {
+                    let d = from.manhattan(to);
+                    if d <= params.max_cheat && after >= before + d + params.min_saving {
+                        cheats += 1;
+                    }
+                }
             }
-        })
+            cheats.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
+
+    const INPUT: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+    #[test]
+    fn example1() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(short_cheats(&input, 64), 1);
+        assert_eq!(short_cheats(&input, 20), 5);
+        assert_eq!(part1(&input), Answer::Int(0));
+    }
+
+    #[test]
+    fn example2() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(long_cheats(&input, 20, 76), 3);
+        assert_eq!(long_cheats(&input, 20, 72), 29);
+        let params = Params {
+            min_saving: 64,
+            max_cheat: 2,
+        };
+        assert_eq!(Day20::part2(&input, &params), Answer::Int(1));
+        assert_eq!(part2(&input), Answer::Int(0));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn short_cheats_are_long_cheats(seed: u64, size in 2usize..12, min_saving in 1usize..20) {
+            let input = parse(&generate::day20(&mut rng(seed), size)).unwrap();
+            prop_assert_eq!(
+                short_cheats(&input, min_saving),
+                long_cheats(&input, 2, min_saving)
+            );
+        }
+    }
 }
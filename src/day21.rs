@@ -1,59 +1,78 @@
+use nom::character::complete::anychar;
+use nom::combinator::map_opt;
+use nom::multi::many0;
+use nom::sequence::terminated;
 use rustc_hash::FxHashMap;
 use std::iter::once;
 
+use crate::answer::Answer;
+use crate::direction::Direction::{self, *};
+use crate::error::{ParseError, Source};
+use crate::parsing::{lines, tag};
+use crate::solution::{Solution, params};
+use crate::trace::trace;
+
+params! {
+    pub struct Params {
+        /// Directional keypad robots of part 1, at least one.
+        pub robots1: usize = 2,
+        /// Directional keypad robots of part 2, at least one.
+        pub robots2: usize = 25,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+pub enum Key {
+    Move(Direction),
     Push,
 }
-use Direction::*;
 
-impl From<Direction> for usize {
-    fn from(value: Direction) -> Self {
+impl From<Key> for usize {
+    fn from(value: Key) -> Self {
         match value {
-            Up => 0,
-            Down => 1,
-            Left => 2,
-            Right => 3,
-            Push => 4,
+            Key::Move(Up) => 0,
+            Key::Move(Down) => 1,
+            Key::Move(Left) => 2,
+            Key::Move(Right) => 3,
+            Key::Push => 4,
         }
     }
 }
 
+fn presses(paths: Vec<Vec<Direction>>) -> Vec<Vec<Key>> {
+    paths
+        .into_iter()
+        .map(|p| {
+            p.into_iter()
+                .map(Key::Move)
+                .chain(once(Key::Push))
+                .collect()
+        })
+        .collect()
+}
+
 pub struct Input {
     codes: Vec<Vec<u8>>,
 }
 
 #[aoc_generator(day21)]
-pub fn parse(input: &str) -> Input {
-    Input {
-        codes: input
-            .lines()
-            .map(|l| {
-                l.strip_suffix("A")
-                    .unwrap()
-                    .chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect()
-            })
-            .collect(),
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let digit = map_opt(anychar, |c| c.to_digit(10).map(|d| d as u8));
+    Ok(Input {
+        codes: Source::new(21, input).run(lines(terminated(many0(digit), tag("A"))))?,
+    })
 }
 
-fn solve_code(code: &Vec<u8>) -> Vec<Vec<Direction>> {
+fn solve_code(code: &[u8]) -> Vec<Vec<Key>> {
     let mut start = 10;
     let mut res = Vec::new();
     for c in code.iter().chain(once(&10)) {
-        let mut paths = get_numerical_path(start, *c as usize);
-        paths.iter_mut().for_each(|p| p.push(Push));
+        let paths = presses(get_numerical_path(start, *c as usize));
         if res.is_empty() {
             res = paths;
         } else {
             let mut new_res = Vec::new();
-            let mut min = std::usize::MAX;
+            let mut min = usize::MAX;
             for old_path in res.into_iter() {
                 let old_len = old_path.len();
                 for path in &paths {
@@ -77,18 +96,22 @@ fn solve_code(code: &Vec<u8>) -> Vec<Vec<Direction>> {
 }
 
 fn shortest_path_size(
-    dirs: Vec<Direction>,
+    dirs: Vec<Key>,
     robots_to_go: usize,
-    visited: &mut FxHashMap<(Vec<Direction>, usize, Direction), usize>,
-    starts_pos: &mut Vec<Direction>,
+    visited: &mut FxHashMap<(Vec<Key>, usize, Key), usize>,
+    starts_pos: &mut Vec<Key>,
 ) -> usize {
     let key = (dirs.clone(), robots_to_go, starts_pos[robots_to_go]);
     if let Some(known) = visited.get(&key) {
+        trace!(robots = robots_to_go, presses = *known, "memo hit");
         return *known;
     }
 
     let v = dirs.iter().fold(0, |acc, dir| {
-        let short_paths = get_directional_path(starts_pos[robots_to_go].into(), (*dir).into());
+        let short_paths = presses(get_directional_path(
+            starts_pos[robots_to_go].into(),
+            (*dir).into(),
+        ));
         starts_pos[robots_to_go] = *dir;
         acc + short_paths
             .into_iter()
@@ -106,11 +129,11 @@ fn shortest_path_size(
     v
 }
 
-fn solve(code: &Vec<u8>, n: usize) -> usize {
+fn solve(code: &[u8], n: usize) -> usize {
     let codes = solve_code(code);
     codes
         .into_iter()
-        .map(|p| shortest_path_size(p, n, &mut FxHashMap::default(), &mut vec![Push; n + 1]))
+        .map(|p| shortest_path_size(p, n, &mut FxHashMap::default(), &mut vec![Key::Push; n + 1]))
         .min()
         .unwrap()
         * code
@@ -120,34 +143,52 @@ fn solve(code: &Vec<u8>, n: usize) -> usize {
             .fold(0, |acc, (i, c)| acc + 10usize.pow(i as u32) * *c as usize)
 }
 
+fn complexity(input: &Input, robots: usize) -> usize {
+    input.codes.iter().fold(0, |acc, c| acc + solve(c, robots))
+}
+
 #[aoc(day21, part1)]
-pub fn part1(input: &Input) -> usize {
-    input.codes.iter().fold(0, |acc, c| acc + solve(c, 2))
+pub fn part1(input: &Input) -> Answer {
+    Day21::part1(input, &Params::default())
 }
 
 #[aoc(day21, part2)]
-pub fn part2(input: &Input) -> usize {
-    input.codes.iter().fold(0, |acc, c| acc + solve(c, 25))
+pub fn part2(input: &Input) -> Answer {
+    Day21::part2(input, &Params::default())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    const INPUT: &str = "029A
-980A
-179A
-456A
-379A";
-    #[test]
-    fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 126384)
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    type Parsed = Input;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        complexity(input, params.robots1).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        complexity(input, params.robots2).into()
+    }
+
+    fn check(_: &Self::Parsed, params: &Params) -> Result<(), String> {
+        if params.robots1 == 0 || params.robots2 == 0 {
+            return Err("robots1 and robots2 must be at least 1".to_owned());
+        }
+        Ok(())
     }
 }
 
 // Why ? I don't know, why not?
 
 fn get_directional_path(start: usize, end: usize) -> Vec<Vec<Direction>> {
-    let mut res = match (start, end) {
+    match (start, end) {
         (0, 0) => vec![vec![]],
         (0, 1) => vec![vec![Down]],
         (0, 2) => vec![vec![Down, Left]],
@@ -174,9 +215,7 @@ fn get_directional_path(start: usize, end: usize) -> Vec<Vec<Direction>> {
         (4, 3) => vec![vec![Down]],
         (4, 4) => vec![vec![]],
         _ => unreachable!(),
-    };
-    res.iter_mut().for_each(|v| v.push(Push));
-    res
+    }
 }
 
 fn get_numerical_path(start: usize, end: usize) -> Vec<Vec<Direction>> {
@@ -425,3 +464,142 @@ fn get_numerical_path(start: usize, end: usize) -> Vec<Vec<Direction>> {
         _ => unreachable!(),
     }
 }
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+    use crate::reference::Reference;
+
+    const NUMERIC: [&str; 4] = ["789", "456", "123", " 0A"];
+    const DIRECTIONAL: [&str; 2] = [" ^A", "<v>"];
+
+    fn key(pad: &[&str], (x, y): (usize, usize)) -> Option<u8> {
+        let key = *pad.get(y)?.as_bytes().get(x)?;
+        (key != b' ').then_some(key)
+    }
+
+    /// Fewest presses typing `code`, searching every position of the arms.
+    fn fewest_presses(code: &[u8], robots: usize) -> usize {
+        let mut arms = vec![(2, 0); robots];
+        arms.push((2, 3));
+        let mut seen = HashSet::from([(arms.clone(), 0)]);
+        let mut queue = VecDeque::from([(arms, 0, 0)]);
+        while let Some((arms, typed, presses)) = queue.pop_front() {
+            if typed == code.len() {
+                return presses;
+            }
+            'press: for mut k in *b"^v<>A" {
+                let (mut arms, mut typed) = (arms.clone(), typed);
+                for (i, arm) in arms.iter_mut().enumerate() {
+                    let pad = if i == robots {
+                        &NUMERIC[..]
+                    } else {
+                        &DIRECTIONAL[..]
+                    };
+                    let (x, y) = *arm;
+                    let moved = match k {
+                        b'A' => {
+                            k = key(pad, *arm).unwrap();
+                            if i == robots {
+                                if k != code[typed] {
+                                    continue 'press;
+                                }
+                                typed += 1;
+                            }
+                            continue;
+                        }
+                        b'^' => y.checked_sub(1).map(|y| (x, y)),
+                        b'v' => Some((x, y + 1)),
+                        b'<' => x.checked_sub(1).map(|x| (x, y)),
+                        _ => Some((x + 1, y)),
+                    };
+                    match moved.filter(|&p| key(pad, p).is_some()) {
+                        Some(p) => *arm = p,
+                        None => continue 'press,
+                    }
+                    break;
+                }
+                if seen.insert((arms.clone(), typed)) {
+                    queue.push_back((arms, typed, presses + 1));
+                }
+            }
+        }
+        unreachable!("{code:?} cannot be typed")
+    }
+
+    fn complexity(input: &Input, robots: usize) -> Answer {
+        input
+            .codes
+            .iter()
+            .map(|code| {
+                let keys: Vec<u8> = code.iter().map(|d| b'0' + d).chain(once(b'A')).collect();
+                let value = code.iter().fold(0, |acc, &d| acc * 10 + d as usize);
+                fewest_presses(&keys, robots) * value
+            })
+            .sum::<usize>()
+            .into()
+    }
+
+    impl Reference for Day21 {
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            complexity(input, params.robots1)
+        }
+
+        /// Only practical with a few robots.
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            complexity(input, params.robots2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
+    const INPUT: &str = "029A
+980A
+179A
+456A
+379A";
+    #[test]
+    fn example1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(126384))
+    }
+
+    #[test]
+    fn robots() {
+        // 029A takes 12 presses on the numeric keypad, 28 through one robot.
+        let params = Params {
+            robots1: 1,
+            ..Params::default()
+        };
+        assert_eq!(
+            <Day21 as Solution>::part1(&parse("029A").unwrap(), &params),
+            Answer::Int(28 * 29)
+        )
+    }
+
+    #[test]
+    fn example2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(154115708116294))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn naive_presses(seed: u64, size in 1usize..4, robots in 1usize..=3) {
+            let input = parse(&generate::day21(&mut rng(seed), size)).unwrap();
+            let params = Params { robots1: robots, ..Params::default() };
+            prop_assert_eq!(
+                <Day21 as Solution>::part1(&input, &params),
+                <Day21 as Reference>::part1(&input, &params)
+            );
+            prop_assert!(part1(&input).int() <= part2(&input).int());
+        }
+    }
+}
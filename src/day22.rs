@@ -0,0 +1,188 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{lines, number};
+use crate::solution::{Solution, params};
+
+params! {
+    pub struct Params {
+        /// Secrets generated by each buyer.
+        pub rounds: usize = 2000,
+    }
+}
+
+const PRUNE: u64 = (1 << 24) - 1;
+/// Number of sequences of four price changes, each in -9..=9.
+const SEQUENCES: usize = 19 * 19 * 19 * 19;
+
+#[aoc_generator(day22)]
+pub fn parse(input: &str) -> Result<Vec<u64>, ParseError> {
+    Source::new(22, input).run(lines(number("a secret number")))
+}
+
+fn next(mut s: u64) -> u64 {
+    s = ((s << 6) ^ s) & PRUNE;
+    s = ((s >> 5) ^ s) & PRUNE;
+    ((s << 11) ^ s) & PRUNE
+}
+
+fn secrets(s: u64) -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(s), |&s| Some(next(s)))
+}
+
+fn nth_secrets(input: &[u64], rounds: usize) -> u64 {
+    input.iter().map(|&s| secrets(s).nth(rounds).unwrap()).sum()
+}
+
+/// Bananas of the best sequence, summed over the buyers.
+///
+/// Sequences index a dense table in base 19, and `seen` records the last
+/// buyer selling on each one so that only its first occurrence counts.
+fn best_sequence(input: &[u64], rounds: usize) -> u32 {
+    let mut bananas = vec![0u32; SEQUENCES];
+    let mut seen = vec![usize::MAX; SEQUENCES];
+    for (buyer, &s) in input.iter().enumerate() {
+        let mut prices = secrets(s).take(rounds + 1).map(|s| (s % 10) as usize);
+        let Some(mut prev) = prices.next() else {
+            continue;
+        };
+        let mut seq = 0;
+        for (i, price) in prices.enumerate() {
+            seq = (seq * 19 + price + 9 - prev) % SEQUENCES;
+            prev = price;
+            if i >= 3 && seen[seq] != buyer {
+                seen[seq] = buyer;
+                bananas[seq] += price as u32;
+            }
+        }
+    }
+    bananas.into_iter().max().unwrap_or(0)
+}
+
+#[aoc(day22, part1)]
+pub fn part1(input: &[u64]) -> Answer {
+    nth_secrets(input, Params::default().rounds).into()
+}
+
+#[aoc(day22, part2)]
+pub fn part2(input: &[u64]) -> Answer {
+    best_sequence(input, Params::default().rounds).into()
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    type Parsed = Vec<u64>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+        nth_secrets(input, params.rounds).into()
+    }
+
+    fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+        best_sequence(input, params.rounds).into()
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Secret following `s`, spelled out as in the puzzle.
+    fn evolve(s: u64) -> u64 {
+        let s = ((s * 64) ^ s) % 16777216;
+        let s = ((s / 32) ^ s) % 16777216;
+        ((s * 2048) ^ s) % 16777216
+    }
+
+    fn prices(s: u64, rounds: usize) -> Vec<i64> {
+        let mut prices = vec![(s % 10) as i64];
+        let mut s = s;
+        for _ in 0..rounds {
+            s = evolve(s);
+            prices.push((s % 10) as i64);
+        }
+        prices
+    }
+
+    impl Reference for Day22 {
+        fn part1(input: &Self::Parsed, params: &Params) -> Answer {
+            input
+                .iter()
+                .map(|&s| (0..params.rounds).fold(s, |s, _| evolve(s)))
+                .sum::<u64>()
+                .into()
+        }
+
+        /// Best total among the sequences of changes seen by any buyer.
+        fn part2(input: &Self::Parsed, params: &Params) -> Answer {
+            let mut bananas: HashMap<Vec<i64>, i64> = HashMap::new();
+            for &s in input {
+                let prices = prices(s, params.rounds);
+                let changes: Vec<i64> = prices.windows(2).map(|w| w[1] - w[0]).collect();
+                let mut seen = HashSet::new();
+                for (i, seq) in changes.windows(4).enumerate() {
+                    if seen.insert(seq) {
+                        *bananas.entry(seq.to_vec()).or_default() += prices[i + 4];
+                    }
+                }
+            }
+            bananas.into_values().max().unwrap_or(0).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
+
+    #[test]
+    fn evolve() {
+        assert_eq!(
+            secrets(123).skip(1).take(4).collect::<Vec<_>>(),
+            vec![15887950, 16495136, 527345, 704524]
+        );
+    }
+
+    #[test]
+    fn example1() {
+        assert_eq!(
+            part1(&parse("1\n10\n100\n2024").unwrap()),
+            Answer::Int(37327623)
+        )
+    }
+
+    #[test]
+    fn example2() {
+        assert_eq!(part2(&parse("1\n2\n3\n2024").unwrap()), Answer::Int(23))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn naive_sequence(seed: u64, size in 1usize..8, rounds in 0usize..60) {
+            let input = parse(&generate::day22(&mut rng(seed), size)).unwrap();
+            let params = Params { rounds };
+            prop_assert_eq!(
+                <Day22 as Solution>::part1(&input, &params),
+                <Day22 as Reference>::part1(&input, &params)
+            );
+            prop_assert_eq!(
+                <Day22 as Solution>::part2(&input, &params),
+                <Day22 as Reference>::part2(&input, &params)
+            );
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use itertools::Itertools;
+use nom::character::complete::char;
+use nom::sequence::separated_pair;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::graph::Graph;
+use crate::parsing::{lines, lowercase};
+use crate::solution::Solution;
+
+#[aoc_generator(day23)]
+pub fn parse(input: &str) -> Result<Graph<String>, ParseError> {
+    let computer = || lowercase("a computer name");
+    let edges =
+        Source::new(23, input).run(lines(separated_pair(computer(), char('-'), computer())))?;
+    Ok(Graph::from_edges(
+        edges.into_iter().map(|(a, b)| (a.to_owned(), b.to_owned())),
+    ))
+}
+
+#[aoc(day23, part1)]
+pub fn part1(input: &Graph<String>) -> Answer {
+    input
+        .triangles()
+        .filter(|t| t.iter().any(|&n| input.name(n).starts_with('t')))
+        .count()
+        .into()
+}
+
+#[aoc(day23, part2)]
+pub fn part2(input: &Graph<String>) -> Answer {
+    input
+        .max_clique()
+        .into_iter()
+        .map(|n| input.name(n))
+        .sorted()
+        .join(",")
+        .into()
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+
+    type Parsed = Graph<String>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    fn is_clique(graph: &Graph<String>, nodes: &[usize]) -> bool {
+        nodes
+            .iter()
+            .tuple_combinations()
+            .all(|(&a, &b)| graph.has_edge(a, b))
+    }
+
+    impl Reference for Day23 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            (0..input.len())
+                .tuple_combinations()
+                .filter(|&(a, b, c)| is_clique(input, &[a, b, c]))
+                .filter(|&(a, b, c)| [a, b, c].iter().any(|&n| input.name(n).starts_with('t')))
+                .count()
+                .into()
+        }
+
+        /// Largest clique among every subset of computers.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            (0..input.len())
+                .powerset()
+                .filter(|nodes| is_clique(input, nodes))
+                .max_by_key(Vec::len)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|n| input.name(n))
+                .sorted()
+                .join(",")
+                .into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
+    const INPUT: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+    #[test]
+    fn example1() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(input.triangles().count(), 12);
+        assert_eq!(part1(&input), Answer::Int(7))
+    }
+
+    #[test]
+    fn example2() {
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::from("co,de,ka,ta"))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn naive_cliques(seed: u64, size in 3usize..12) {
+            let graph = parse(&generate::day23(&mut rng(seed), size)).unwrap();
+            prop_assert_eq!(part1(&graph), <Day23 as Reference>::part1(&graph, &()));
+            prop_assert_eq!(part2(&graph), <Day23 as Reference>::part2(&graph, &()));
+        }
+    }
+}
@@ -0,0 +1,508 @@
+use itertools::Itertools;
+use nom::branch::alt;
+use nom::character::complete::{alphanumeric1, char, space0, space1};
+use nom::combinator::{consumed, value};
+use nom::error::context;
+use nom::sequence::{pair, preceded, separated_pair, tuple};
+use rustc_hash::FxHashMap;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{blank_line, lines, tag};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    pub fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a & b,
+            Op::Or => a | b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gate {
+    pub op: Op,
+    pub a: usize,
+    pub b: usize,
+    pub out: usize,
+}
+
+/// Why gates do not form a circuit, along with the index of the faulty gate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitError {
+    DrivenTwice(usize),
+    Loop(usize),
+}
+
+/// Combinational circuit over named wires, gates kept in topological order.
+///
+/// Buses are the wires sharing a one letter prefix followed by their bit
+/// number, such as `x00`, `x01`, ...
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    names: Vec<String>,
+    index: FxHashMap<String, usize>,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    /// Builds a circuit from `(a, op, b, out)` gates given in any order.
+    pub fn new<'a>(
+        gates: impl IntoIterator<Item = (&'a str, Op, &'a str, &'a str)>,
+    ) -> Result<Self, CircuitError> {
+        let mut names = Vec::new();
+        let mut index = FxHashMap::default();
+        let mut wire = |name: &str| {
+            *index.entry(name.to_owned()).or_insert_with(|| {
+                names.push(name.to_owned());
+                names.len() - 1
+            })
+        };
+        let gates: Vec<Gate> = gates
+            .into_iter()
+            .map(|(a, op, b, out)| Gate {
+                op,
+                a: wire(a),
+                b: wire(b),
+                out: wire(out),
+            })
+            .collect();
+
+        let mut driver = vec![None; names.len()];
+        for (i, g) in gates.iter().enumerate() {
+            if driver[g.out].replace(i).is_some() {
+                return Err(CircuitError::DrivenTwice(i));
+            }
+        }
+        let mut pending: Vec<usize> = gates
+            .iter()
+            .map(|g| [g.a, g.b].iter().filter(|&&w| driver[w].is_some()).count())
+            .collect();
+        let mut users = vec![Vec::new(); names.len()];
+        for (i, g) in gates.iter().enumerate() {
+            users[g.a].push(i);
+            if g.b != g.a {
+                users[g.b].push(i);
+            }
+        }
+        let mut order: Vec<usize> = (0..gates.len()).filter(|&i| pending[i] == 0).collect();
+        let mut k = 0;
+        while let Some(&i) = order.get(k) {
+            k += 1;
+            for &u in &users[gates[i].out] {
+                let g = gates[u];
+                pending[u] -= usize::from(g.a == gates[i].out) + usize::from(g.b == gates[i].out);
+                if pending[u] == 0 {
+                    order.push(u);
+                }
+            }
+        }
+        if order.len() < gates.len() {
+            let stuck = (0..gates.len()).find(|&i| pending[i] > 0).unwrap();
+            return Err(CircuitError::Loop(stuck));
+        }
+
+        Ok(Circuit {
+            gates: order.into_iter().map(|i| gates[i]).collect(),
+            names,
+            index,
+        })
+    }
+
+    pub fn wire(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn name(&self, wire: usize) -> &str {
+        &self.names[wire]
+    }
+
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    /// `(bit, wire)` pairs of a bus, in increasing bit order.
+    pub fn bus(&self, prefix: char) -> Vec<(u32, usize)> {
+        self.names
+            .iter()
+            .enumerate()
+            .filter_map(|(w, name)| {
+                let bit = name.strip_prefix(prefix)?.parse().ok()?;
+                Some((bit, w))
+            })
+            .sorted()
+            .collect()
+    }
+
+    /// Value of every wire once `inputs` are set, `None` for undriven wires.
+    pub fn run(&self, inputs: impl IntoIterator<Item = (usize, bool)>) -> Vec<Option<bool>> {
+        let mut values = vec![None; self.names.len()];
+        for (w, v) in inputs {
+            values[w] = Some(v);
+        }
+        for g in &self.gates {
+            if let (Some(a), Some(b)) = (values[g.a], values[g.b]) {
+                values[g.out] = Some(g.op.apply(a, b));
+            }
+        }
+        values
+    }
+
+    /// Number on a bus, `None` if one of its wires has no value or is past
+    /// bit 63.
+    pub fn read(&self, values: &[Option<bool>], prefix: char) -> Option<u64> {
+        self.bus(prefix).into_iter().try_fold(0, |acc, (bit, w)| {
+            Some(acc | u64::from(values[w]?).checked_shl(bit)?)
+        })
+    }
+
+    /// Sets the `x` and `y` buses and reads the `z` one.
+    pub fn simulate(&self, x: u64, y: u64) -> Option<u64> {
+        let bits = |prefix, n: u64| {
+            self.bus(prefix)
+                .into_iter()
+                .map(move |(bit, w)| (w, n.checked_shr(bit).unwrap_or(0) & 1 == 1))
+        };
+        let values = self.run(bits('x', x).chain(bits('y', y)));
+        self.read(&values, 'z')
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Input {
+    circuit: Circuit,
+    initial: Vec<(usize, bool)>,
+}
+
+#[aoc_generator(day24)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(24, input);
+    let wire = || context("a wire", alphanumeric1);
+    let bit = context(
+        "0 or 1",
+        alt((value(false, char('0')), value(true, char('1')))),
+    );
+    let op = context(
+        "one of AND, OR, XOR",
+        alt((
+            value(Op::And, tag("AND")),
+            value(Op::Or, tag("OR")),
+            value(Op::Xor, tag("XOR")),
+        )),
+    );
+    let gate = consumed(tuple((
+        wire(),
+        preceded(space1, op),
+        preceded(space1, wire()),
+        preceded(tuple((space1, tag("->"), space1)), wire()),
+    )));
+    let (values, gates) = src.run(separated_pair(
+        lines(separated_pair(wire(), pair(char(':'), space0), bit)),
+        blank_line,
+        lines(gate),
+    ))?;
+    // Every mention of a wire, in input order.
+    let wires = values
+        .iter()
+        .map(|&(w, _)| w)
+        .chain(gates.iter().flat_map(|&(_, (a, _, b, out))| [a, b, out]));
+    // Buses are read into 64-bit numbers.
+    let past_63 = |w: &str| {
+        w.strip_prefix(['x', 'y', 'z'])
+            .and_then(|bit| bit.parse::<u32>().ok())
+            .is_some_and(|bit| bit >= 64)
+    };
+    if let Some(w) = wires.clone().find(|w| past_63(w)) {
+        return Err(src.error(w, "a bus bit below 64"));
+    }
+    let circuit = Circuit::new(gates.iter().map(|&(_, gate)| gate)).map_err(|e| match e {
+        CircuitError::DrivenTwice(i) => src.error(gates[i].0, "a wire driven by a single gate"),
+        CircuitError::Loop(i) => src.error(gates[i].0, "a gate outside of a loop"),
+    })?;
+
+    let initial = values
+        .iter()
+        .map(|&(w, v)| {
+            let w = circuit
+                .wire(w)
+                .ok_or_else(|| src.error(w, "a wire connected to a gate"))?;
+            Ok((w, v))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    let set = circuit.run(initial.iter().copied());
+    if let Some((_, z)) = circuit
+        .bus('z')
+        .into_iter()
+        .find(|&(_, w)| set[w].is_none())
+    {
+        let at = wires.clone().find(|&w| w == circuit.name(z)).unwrap();
+        return Err(src.error(at, "a z wire given a value by the inputs"));
+    }
+    Ok(Input { circuit, initial })
+}
+
+/// Outputs breaking the ripple-carry adder pattern.
+///
+/// Each bit `i > 0` computes `z = (x ^ y) ^ carry` and
+/// `carry' = (x & y) | ((x ^ y) & carry)`, the last `z` being the final carry.
+fn miswired(c: &Circuit) -> Vec<&str> {
+    let last = c.bus('z').last().map(|&(_, w)| w);
+    let is_input = |w| c.name(w).starts_with(['x', 'y']);
+    let feeds = |w, op| {
+        c.gates()
+            .iter()
+            .any(|g| g.op == op && (g.a == w || g.b == w))
+    };
+    c.gates()
+        .iter()
+        .filter(|g| {
+            let from_inputs = is_input(g.a) && is_input(g.b);
+            let first = from_inputs && c.name(g.a)[1..].parse() == Ok(0);
+            if Some(g.out) == last {
+                g.op != Op::Or
+            } else if c.name(g.out).starts_with('z') {
+                g.op != Op::Xor
+            } else {
+                match g.op {
+                    Op::Xor => !from_inputs || !feeds(g.out, Op::Xor),
+                    Op::And => !first && !feeds(g.out, Op::Or),
+                    Op::Or => false,
+                }
+            }
+        })
+        .map(|g| c.name(g.out))
+        .sorted()
+        .collect()
+}
+
+#[aoc(day24, part1)]
+pub fn part1(input: &Input) -> Answer {
+    let values = input.circuit.run(input.initial.iter().copied());
+    input.circuit.read(&values, 'z').unwrap().into()
+}
+
+#[aoc(day24, part2)]
+pub fn part2(input: &Input) -> Answer {
+    miswired(&input.circuit).join(",").into()
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Whether the circuit adds, trying every bit alone and along carries.
+    fn adds(c: &Circuit) -> bool {
+        let bits = c.bus('x').len();
+        let all = (1u64 << bits) - 1;
+        (0..bits)
+            .flat_map(|i| [(1 << i, 0), (0, 1 << i), (1 << i, 1 << i), (all, 1 << i)])
+            .all(|(x, y)| c.simulate(x, y) == Some(x + y))
+    }
+
+    /// The circuit with the outputs of each pair of wires exchanged.
+    fn swapped(c: &Circuit, swaps: &[(usize, usize)]) -> Option<Circuit> {
+        let out = |w| {
+            swaps.iter().fold(w, |w, &(a, b)| match w {
+                _ if w == a => b,
+                _ if w == b => a,
+                _ => w,
+            })
+        };
+        Circuit::new(
+            c.gates()
+                .iter()
+                .map(|g| (c.name(g.a), g.op, c.name(g.b), c.name(out(g.out)))),
+        )
+        .ok()
+    }
+
+    impl Reference for Day24 {
+        /// Evaluates the gates over and over until no wire changes.
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let c = &input.circuit;
+            let mut values: FxHashMap<&str, bool> =
+                input.initial.iter().map(|&(w, v)| (c.name(w), v)).collect();
+            loop {
+                let known = values.len();
+                for g in c.gates().iter().rev() {
+                    if let (Some(&a), Some(&b)) = (values.get(c.name(g.a)), values.get(c.name(g.b)))
+                    {
+                        values.insert(c.name(g.out), g.op.apply(a, b));
+                    }
+                }
+                if values.len() == known {
+                    break;
+                }
+            }
+            values
+                .iter()
+                .filter_map(|(w, &v)| {
+                    Some(u64::from(v) << w.strip_prefix('z')?.parse::<u32>().ok()?)
+                })
+                .sum::<u64>()
+                .into()
+        }
+
+        /// Tries every set of up to four swaps, fewest first, only practical on
+        /// small circuits.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let c = &input.circuit;
+            let pairs: Vec<(usize, usize)> = c
+                .gates()
+                .iter()
+                .map(|g| g.out)
+                .tuple_combinations()
+                .collect();
+            for k in 0..=4 {
+                for swaps in pairs.iter().copied().combinations(k) {
+                    let wires: Vec<usize> = swaps.iter().flat_map(|&(a, b)| [a, b]).collect();
+                    if wires.iter().all_unique() && swapped(c, &swaps).is_some_and(|c| adds(&c)) {
+                        return wires.iter().map(|&w| c.name(w)).sorted().join(",").into();
+                    }
+                }
+            }
+            unreachable!("no four swaps make an adder")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
+    const INPUT: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
+    /// Ripple-carry adder of `n` bits, with the given outputs swapped.
+    fn adder(n: usize, swaps: &[(&str, &str)]) -> String {
+        let mut gates = vec![
+            "x00 XOR y00 -> z00".to_owned(),
+            "x00 AND y00 -> cry00".to_owned(),
+        ];
+        for i in 1..n {
+            let carry = format!("cry{:02}", i - 1);
+            gates.extend([
+                format!("x{i:02} XOR y{i:02} -> xor{i:02}"),
+                format!("y{i:02} AND x{i:02} -> and{i:02}"),
+                format!("xor{i:02} XOR {carry} -> z{i:02}"),
+                format!("{carry} AND xor{i:02} -> car{i:02}"),
+                format!("and{i:02} OR car{i:02} -> cry{i:02}"),
+            ]);
+        }
+        let last = format!("cry{:02}", n - 1);
+        let gates = gates.iter().map(|g| {
+            let (lhs, out) = g.split_once(" -> ").unwrap();
+            let out = if out == last {
+                format!("z{n:02}")
+            } else {
+                out.to_owned()
+            };
+            let out = swaps.iter().fold(out, |out, &(a, b)| match out {
+                o if o == a => b.to_owned(),
+                o if o == b => a.to_owned(),
+                o => o,
+            });
+            format!("{lhs} -> {out}")
+        });
+        let values = (0..n)
+            .flat_map(|i| [format!("x{i:02}: 0"), format!("y{i:02}: 0")])
+            .join("\n");
+        format!("{values}\n\n{}", gates.rev().join("\n"))
+    }
+
+    #[test]
+    fn example1() {
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(4))
+    }
+
+    #[test]
+    fn example2() {
+        let input = parse(&adder(12, &[])).unwrap();
+        assert_eq!(input.circuit.simulate(1234, 2345), Some(3579));
+        assert_eq!(input.circuit.simulate(4095, 1), Some(4096));
+        assert_eq!(part2(&input), Answer::from(""));
+
+        let swaps = [
+            ("z03", "and03"),
+            ("xor07", "and07"),
+            ("z09", "cry09"),
+            ("car05", "z05"),
+        ];
+        let input = parse(&adder(12, &swaps)).unwrap();
+        assert_ne!(input.circuit.simulate(4095, 1), Some(4096));
+        assert_eq!(
+            part2(&input),
+            Answer::from("and03,and07,car05,cry09,xor07,z03,z05,z09")
+        );
+    }
+
+    #[test]
+    fn bad_input() {
+        let err = parse("x00: 1\n\nx00 AND z00 -> z00").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+        let err = parse("x00: 1\n\nx00 NOT x00 -> z00").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 5));
+        let err = parse("x00: 1\n\nx00 AND q00 -> z00").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 16));
+        assert_eq!(err.expected, "a z wire given a value by the inputs");
+        let err = parse("x00: 1\nx64: 0\n\nx00 AND x64 -> z00").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        let err = parse("x00: 1\n\nx00 AND x00 -> z64").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 16));
+        assert_eq!(err.expected, "a bus bit below 64");
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn generated_adder(seed: u64, size in 2usize..20) {
+            let input = parse(&generate::day24(&mut rng(seed), size)).unwrap();
+            let values = input.circuit.run(input.initial.iter().copied());
+            let (x, y) = (input.circuit.read(&values, 'x'), input.circuit.read(&values, 'y'));
+            prop_assert_eq!(part1(&input), (x.unwrap() + y.unwrap()).into());
+            prop_assert_eq!(part2(&input), Answer::from(""));
+        }
+    }
+}
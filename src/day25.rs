@@ -0,0 +1,175 @@
+use itertools::Itertools;
+use nom::combinator::consumed;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{blocks, char_map};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone)]
+pub struct Input {
+    locks: Vec<Vec<usize>>,
+    keys: Vec<Vec<usize>>,
+    /// Room left for pins between the top and bottom rows.
+    space: usize,
+}
+
+#[aoc_generator(day25)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(25, input);
+    let schematic = char_map("# or .", |_, c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    });
+    let mut locks = Vec::new();
+    let mut keys = Vec::new();
+    let mut size = None;
+    for (block, grid) in src.run(blocks(consumed(schematic)))? {
+        let dims = (grid.width(), grid.height());
+        if *size.get_or_insert(dims) != dims || dims.1 < 2 {
+            return Err(src.error(block, "a schematic of the same size as the first"));
+        }
+        let schematics = if grid.row(0).iter().all(|&b| b) {
+            &mut locks
+        } else if grid.row(grid.height() - 1).iter().all(|&b| b) {
+            &mut keys
+        } else {
+            return Err(src.error(block, "a lock or a key"));
+        };
+        schematics.push(
+            (0..grid.width())
+                .map(|j| grid.column(j).filter(|&&b| b).count() - 1)
+                .collect(),
+        );
+    }
+    Ok(Input {
+        locks,
+        keys,
+        space: size.map_or(0, |(_, h)| h - 2),
+    })
+}
+
+#[aoc(day25, part1)]
+pub fn part1(input: &Input) -> Answer {
+    input
+        .locks
+        .iter()
+        .cartesian_product(&input.keys)
+        .filter(|(l, k)| l.iter().zip(k.iter()).all(|(a, b)| a + b <= input.space))
+        .count()
+        .into()
+}
+
+/// There is no second puzzle on the last day.
+#[aoc(day25, part2)]
+pub fn part2(_: &Input) -> Answer {
+    "Merry Christmas!".into()
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    impl Reference for Day25 {
+        /// Pairs whose pins share no row, counting the solid top and bottom rows.
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let rows = input.space + 2;
+            let mut fits = 0;
+            for lock in &input.locks {
+                for key in &input.keys {
+                    let overlap = lock
+                        .iter()
+                        .zip(key)
+                        .any(|(&l, &k)| (0..rows).any(|row| row <= l && row + k + 1 >= rows));
+                    fits += usize::from(!overlap);
+                }
+            }
+            fits.into()
+        }
+
+        fn part2(_: &Self::Parsed, _: &()) -> Answer {
+            "Merry Christmas!".into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const INPUT: &str = "#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
+    #[test]
+    fn example1() {
+        let input = parse(INPUT).unwrap();
+        assert_eq!(input.locks[0], vec![0, 5, 3, 4, 3]);
+        assert_eq!(input.keys[0], vec![5, 0, 2, 1, 3]);
+        assert_eq!(part1(&input), Answer::Int(3))
+    }
+
+    #[test]
+    fn bad_input() {
+        let err = parse("#####\n.....\n\n.....\n.....").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 1));
+        assert_eq!(err.expected, "a lock or a key");
+    }
+}
@@ -1,5 +1,4 @@
 use nom::{
-    IResult,
     branch::alt,
     bytes::complete::{tag, take},
     character::complete::{char, u32},
@@ -8,6 +7,11 @@ use nom::{
     sequence::{delimited, tuple},
 };
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::PResult;
+use crate::solution::Solution;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Element {
     Mul(u32, u32),
@@ -16,7 +20,7 @@ pub enum Element {
     Gibberish,
 }
 
-fn parse_mul(input: &str) -> IResult<&str, Element> {
+fn parse_mul(input: &str) -> PResult<'_, Element> {
     map(
         delimited(tag("mul("), tuple((u32, char(','), u32)), char(')')),
         |(a, _, b)| Element::Mul(a, b),
@@ -24,8 +28,8 @@ fn parse_mul(input: &str) -> IResult<&str, Element> {
 }
 
 #[aoc_generator(day3)]
-pub fn parse(input: &str) -> Vec<Element> {
-    fold_many0(
+pub fn parse(input: &str) -> Result<Vec<Element>, ParseError> {
+    Source::new(3, input).run(fold_many0(
         alt((
             parse_mul,
             map(tag("do()"), |_| Element::Do),
@@ -39,24 +43,25 @@ pub fn parse(input: &str) -> Vec<Element> {
             }
             acc
         },
-    )(input)
-    .unwrap()
-    .1
+    ))
 }
 
 #[aoc(day3, part1)]
-pub fn part1(input: &[Element]) -> u32 {
-    input.iter().fold(0, |acc, elem| {
-        if let Element::Mul(a, b) = elem {
-            acc + a * b
-        } else {
-            acc
-        }
-    })
+pub fn part1(input: &[Element]) -> Answer {
+    input
+        .iter()
+        .fold(0, |acc, elem| {
+            if let Element::Mul(a, b) = elem {
+                acc + a * b
+            } else {
+                acc
+            }
+        })
+        .into()
 }
 
 #[aoc(day3, part2)]
-pub fn part2(input: &[Element]) -> u32 {
+pub fn part2(input: &[Element]) -> Answer {
     input
         .iter()
         .fold((0, true), |mut acc, elem| {
@@ -69,6 +74,60 @@ pub fn part2(input: &[Element]) -> u32 {
             acc
         })
         .0
+        .into()
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Parsed = Vec<Element>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    impl Reference for Day3 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let mut sum = 0;
+            for elem in input {
+                if let Element::Mul(a, b) = elem {
+                    sum += a * b;
+                }
+            }
+            sum.into()
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let mut sum = 0;
+            let mut enabled = true;
+            for elem in input {
+                match elem {
+                    Element::Mul(a, b) if enabled => sum += a * b,
+                    Element::Do => enabled = true,
+                    Element::Dont => enabled = false,
+                    _ => (),
+                }
+            }
+            sum.into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -78,19 +137,22 @@ mod tests {
     #[test]
     fn example1() {
         assert_eq!(
-            part1(&parse(
-                "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))"
-            )),
-            161
+            part1(
+                &parse("xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))")
+                    .unwrap()
+            ),
+            Answer::Int(161)
         )
     }
+
     #[test]
     fn example2() {
         assert_eq!(
-            part2(&parse(
-                "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"
-            )),
-            48
+            part2(
+                &parse("xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))")
+                    .unwrap()
+            ),
+            Answer::Int(48)
         )
     }
 }
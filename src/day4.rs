@@ -1,48 +1,40 @@
-use itertools::Itertools;
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::grid::{DIRS8, Grid};
+use crate::pos::Vec2;
+use crate::solution::Solution;
 
 #[aoc_generator(day4)]
-pub fn parse(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|l| l.chars().collect()).collect()
+pub fn parse(input: &str) -> Result<Grid<char>, ParseError> {
+    Source::new(4, input).grid(input, "one of XMAS", |_, c| "XMAS".contains(c).then_some(c))
 }
 #[aoc(day4, part1)]
-pub fn part1(input: &[Vec<char>]) -> usize {
-    let mut res = (0..input[0].len())
-        .cartesian_product(0..input.len())
-        .filter(|(x, y)| input[*y][*x] == 'X')
-        .fold(Vec::new(), |mut acc, (x, y)| {
-            acc.extend(
-                (-1..=1isize)
-                    .cartesian_product(-1..=1isize)
-                    .filter(|(dx, dy)| (dx, dy) != (&0, &0))
-                    .map(|(dx, dy)| (x, y, dx, dy)),
-            );
-            acc
-        });
-    ['M', 'A', 'S'].iter().for_each(|c| {
-        res.retain_mut(|(x, y, dx, dy)| {
-            *x = x.wrapping_add_signed(*dx);
-            *y = y.wrapping_add_signed(*dy);
-            if (*x >= input[0].len()) || (*y >= input.len()) {
-                return false;
-            }
-            input[*y][*x] == *c
-        });
-    });
-    res.len()
+pub fn part1(input: &Grid<char>) -> Answer {
+    input
+        .iter()
+        .filter(|(_, c)| **c == 'X')
+        .map(|(p, _)| {
+            DIRS8
+                .iter()
+                .filter(|&&d| input.ray(p, d).map(|(_, c)| *c).take(3).eq(['M', 'A', 'S']))
+                .count()
+        })
+        .sum::<usize>()
+        .into()
 }
 
 #[aoc(day4, part2)]
-pub fn part2(input: &[Vec<char>]) -> usize {
-    (1..input[0].len() - 1)
-        .cartesian_product(1..input.len() - 1)
-        .filter(|(x, y)| input[*y][*x] == 'A')
-        .filter(|(x, y)| {
-            let around = [
-                input[y - 1][x - 1],
-                input[y + 1][x - 1],
-                input[y - 1][x + 1],
-                input[y + 1][x + 1],
-            ];
+pub fn part2(input: &Grid<char>) -> Answer {
+    input
+        .iter()
+        .filter(|(_, c)| **c == 'A')
+        .filter(|(p, _)| {
+            let around = [(-1, -1), (-1, 1), (1, -1), (1, 1)].map(|(x, y)| {
+                input
+                    .step(*p, Vec2::new(x, y))
+                    .map(|np| input[np])
+                    .unwrap_or('.')
+            });
 
             [
                 ['M', 'M', 'S', 'S'],
@@ -53,7 +45,77 @@ pub fn part2(input: &[Vec<char>]) -> usize {
             .contains(&around)
         })
         .count()
+        .into()
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Parsed = Grid<char>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
 }
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Letters read from `(x, y)` by steps of `(dx, dy)`, off the grid ones left out.
+    fn word(
+        grid: &Grid<char>,
+        (x, y): (isize, isize),
+        (dx, dy): (isize, isize),
+        len: isize,
+    ) -> String {
+        (0..len)
+            .filter_map(|i| Vec2::new(x + i * dx, y + i * dy).to_pos())
+            .filter_map(|p| grid.get(p))
+            .collect()
+    }
+
+    impl Reference for Day4 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let mut count = 0;
+            for p in input.positions() {
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        let start = (p.x as isize, p.y as isize);
+                        count += usize::from(word(input, start, (dx, dy), 4) == "XMAS");
+                    }
+                }
+            }
+            count.into()
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let mas = |w: String| w == "MAS" || w == "SAM";
+            input
+                .positions()
+                .filter(|p| {
+                    let (x, y) = (p.x as isize, p.y as isize);
+                    mas(word(input, (x - 1, y - 1), (1, 1), 3))
+                        && mas(word(input, (x + 1, y - 1), (-1, 1), 3))
+                })
+                .count()
+                .into()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,10 +131,11 @@ MAMMMXMMMM
 MXMXAXMASX";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 18)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(18))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 9)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(9))
     }
 }
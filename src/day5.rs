@@ -1,6 +1,13 @@
 use itertools::Itertools;
+use nom::character::complete::char;
+use nom::sequence::separated_pair;
 use rustc_hash::FxHashSet as HashSet;
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::{blank_line, commas, lines, number};
+use crate::solution::Solution;
+
 #[derive(Debug, Clone)]
 pub struct Input {
     rules: HashSet<(usize, usize)>,
@@ -20,23 +27,21 @@ impl Input {
 }
 
 #[aoc_generator(day5)]
-pub fn parse(input: &str) -> Input {
-    let (one, two) = input.split_once("\n\n").unwrap();
-    Input {
-        rules: one
-            .lines()
-            .map(|l| l.split_once('|').unwrap())
-            .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
-            .collect(),
-        updates: two
-            .lines()
-            .map(|l| l.split(",").map(|s| s.parse::<usize>().unwrap()).collect())
-            .collect(),
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let page = || number("a page number");
+    let (rules, updates) = Source::new(5, input).run(separated_pair(
+        lines(separated_pair(page(), char('|'), page())),
+        blank_line,
+        lines(commas(page())),
+    ))?;
+    Ok(Input {
+        rules: rules.into_iter().collect(),
+        updates,
+    })
 }
 
 #[aoc(day5, part1)]
-pub fn part1(input: &Input) -> usize {
+pub fn part1(input: &Input) -> Answer {
     input
         .updates
         .iter()
@@ -46,10 +51,11 @@ pub fn part1(input: &Input) -> usize {
                 .all(|(&a, &b)| input.rules.contains(&(a, b)))
         })
         .fold(0, |acc, u| acc + *u.get(u.len() / 2).unwrap())
+        .into()
 }
 
 #[aoc(day5, part2)]
-pub fn part2(input: &Input) -> usize {
+pub fn part2(input: &Input) -> Answer {
     input
         .updates
         .iter()
@@ -65,6 +71,73 @@ pub fn part2(input: &Input) -> usize {
                 .nth(u.len() / 2)
                 .unwrap()
         })
+        .into()
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Whether no page comes after one it must be printed before.
+    fn ordered(input: &Input, update: &[usize]) -> bool {
+        update
+            .iter()
+            .tuple_combinations()
+            .all(|(&a, &b)| !input.rules.contains(&(b, a)))
+    }
+
+    impl Reference for Day5 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            input
+                .updates
+                .iter()
+                .filter(|u| ordered(input, u))
+                .map(|u| u[u.len() / 2])
+                .sum::<usize>()
+                .into()
+        }
+
+        /// Bubble sorts the unordered updates, swapping neighbours breaking a rule.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            input
+                .updates
+                .iter()
+                .filter(|u| !ordered(input, u))
+                .map(|u| {
+                    let mut u = u.clone();
+                    while let Some(i) =
+                        (1..u.len()).find(|&i| input.rules.contains(&(u[i], u[i - 1])))
+                    {
+                        u.swap(i - 1, i);
+                    }
+                    u[u.len() / 2]
+                })
+                .sum::<usize>()
+                .into()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -100,10 +173,11 @@ mod tests {
 97,13,75,29,47";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 143)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(143))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 123)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(123))
     }
 }
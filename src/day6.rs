@@ -1,54 +1,14 @@
-use bitflags::bitflags;
 use rustc_hash::FxHashSet as HashSet;
 
-bitflags! {
-   #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-   struct GuardDirections: u8 {
-       const UP = 1;
-       const DOWN = 1 << 1;
-       const LEFT = 1 << 2;
-       const RIGHT = 1 << 3;
-   }
-}
-
-impl From<Direction> for GuardDirections {
-    fn from(direction: Direction) -> Self {
-        match direction {
-            Direction::Up => GuardDirections::UP,
-            Direction::Down => GuardDirections::DOWN,
-            Direction::Left => GuardDirections::LEFT,
-            Direction::Right => GuardDirections::RIGHT,
-        }
-    }
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    pub fn get_delta(&self) -> (isize, isize) {
-        match self {
-            Direction::Up => (-1, 0),
-            Direction::Down => (1, 0),
-            Direction::Left => (0, -1),
-            Direction::Right => (0, 1),
-        }
-    }
-
-    pub fn rotate(&self) -> Self {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-}
+use crate::answer::Answer;
+use crate::direction::{Direction, Directions};
+use crate::error::{ParseError, Source};
+use crate::grid::Grid;
+use crate::par::*;
+use crate::pos::Pos;
+use crate::solution::Solution;
+use crate::trace::{debug, trace};
+use crate::viz::{Animate, Frame, Glyph, Rgb};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Cell {
@@ -59,140 +19,236 @@ pub enum Cell {
 
 #[derive(Debug, Clone)]
 pub struct Input {
-    grid: Vec<Vec<Cell>>,
-    w: usize,
-    h: usize,
-    guard_start: (usize, usize, Direction),
+    grid: Grid<Cell>,
+    guard_start: (Pos, Direction),
 }
 
 #[aoc_generator(day6)]
-pub fn parse(input: &str) -> Input {
-    let mut guard = None;
-    let grid: Vec<Vec<Cell>> = input
-        .lines()
-        .enumerate()
-        .map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(j, c)| match c {
-                    '.' => Cell::Empty,
-                    '#' => Cell::Used,
-                    _ => {
-                        let direction = match c {
-                            '^' => Direction::Up,
-                            '>' => Direction::Right,
-                            '<' => Direction::Left,
-                            'v' => Direction::Down,
-                            _ => unreachable!(),
-                        };
-                        guard = Some((i, j, direction));
-                        Cell::Guard(direction)
-                    }
-                })
-                .collect()
-        })
-        .collect();
-    Input {
-        w: grid[0].len(),
-        h: grid.len(),
-        grid,
-        guard_start: guard.unwrap(),
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let src = Source::new(6, input);
+    let mut guards = Vec::new();
+    let grid = src.grid(input, "one of .#^v<>", |p, c| match c {
+        '.' => Some(Cell::Empty),
+        '#' => Some(Cell::Used),
+        _ => {
+            let direction = Direction::from_arrow(c)?;
+            guards.push((p, direction));
+            Some(Cell::Guard(direction))
+        }
+    })?;
+    let guard_start = match guards[..] {
+        [] => return Err(src.eof("a guard")),
+        [guard] => guard,
+        [_, (p, _), ..] => {
+            let row = input.lines().nth(p.y).unwrap_or_default();
+            let k = row.char_indices().nth(p.x).map_or(row.len(), |(k, _)| k);
+            // Arrows are a single byte.
+            return Err(src.error(&row[k..k + 1], "a single guard"));
+        }
+    };
+    Ok(Input { grid, guard_start })
+}
+
+/// Guard after one move, turning right in front of an obstacle, `None` once
+/// they leave the map.
+fn patrol_step(grid: &Grid<Cell>, (p, d): (Pos, Direction)) -> Option<(Pos, Direction)> {
+    let next = grid.step(p, d.delta())?;
+    trace!(guard = %p, facing = ?d, "guard step");
+    Some(if grid[next] == Cell::Used {
+        (p, d.clockwise())
+    } else {
+        (next, d)
+    })
 }
 
 #[aoc(day6, part1)]
-pub fn part1(input: &Input) -> usize {
-    let mut visited = vec![vec![GuardDirections::empty(); input.w]; input.h];
+pub fn part1(input: &Input) -> Answer {
+    let mut visited = Grid::new(input.grid.width(), input.grid.height(), Directions::empty());
 
     let mut guard = input.guard_start;
-    visited[guard.0][guard.1] = guard.2.into();
+    visited[guard.0] = guard.1.into();
     let mut res = 1;
 
-    loop {
-        let next_pos = (
-            guard.0.wrapping_add_signed(guard.2.get_delta().0),
-            guard.1.wrapping_add_signed(guard.2.get_delta().1),
-        );
-        if next_pos.0 >= input.h || next_pos.1 >= input.w {
+    while let Some(next) = patrol_step(&input.grid, guard) {
+        guard = next;
+        let seen = &mut visited[guard.0];
+        if seen.contains(guard.1.into()) {
+            debug!(guard = %guard.0, facing = ?guard.1, "guard loops");
             break;
         }
 
-        if let Cell::Used = input.grid[next_pos.0][next_pos.1] {
-            guard = (guard.0, guard.1, guard.2.rotate());
-        } else {
-            guard = (next_pos.0, next_pos.1, guard.2);
-        }
-        if visited[guard.0][guard.1].contains(guard.2.into()) {
-            break;
-        }
-
-        if visited[guard.0][guard.1].is_empty() {
+        if seen.is_empty() {
             res += 1;
         }
-        visited[guard.0][guard.1].insert(guard.2.into());
+        seen.insert(guard.1.into());
     }
-    res
+    res.into()
 }
 
 #[aoc(day6, part2)]
-pub fn part2(input: &Input) -> usize {
-    let mut visited = vec![vec![GuardDirections::empty(); input.w]; input.h];
+pub fn part2(input: &Input) -> Answer {
+    obstructions(input).len().into()
+}
+
+/// Tiles where a new obstruction would trap the guard in a loop.
+fn obstructions(input: &Input) -> HashSet<Pos> {
+    let mut visited = Grid::new(input.grid.width(), input.grid.height(), Directions::empty());
 
     let mut guard = input.guard_start;
-    visited[guard.0][guard.1] = guard.2.into();
-    let mut res = HashSet::default();
+    visited[guard.0] = guard.1.into();
+    // Tiles first reached along the patrol, along with the guard about to step on them.
+    let mut candidates = Vec::new();
+    let mut looped = false;
 
-    loop {
-        let next_pos = (
-            guard.0.wrapping_add_signed(guard.2.get_delta().0),
-            guard.1.wrapping_add_signed(guard.2.get_delta().1),
-        );
-        if next_pos.0 >= input.h || next_pos.1 >= input.w {
+    while let Some(next_pos) = input.grid.step(guard.0, guard.1.delta()) {
+        trace!(guard = %guard.0, facing = ?guard.1, "guard step");
+        if let Cell::Used = input.grid[next_pos] {
+            guard.1 = guard.1.clockwise();
+        } else {
+            if visited[next_pos].is_empty() {
+                candidates.push((guard, next_pos));
+            }
+            guard.0 = next_pos;
+        }
+
+        let seen = &mut visited[guard.0];
+        if seen.contains(guard.1.into()) {
+            looped = true;
             break;
         }
+        seen.insert(guard.1.into());
+    }
+
+    let mut blocks: HashSet<Pos> = candidates
+        .par_iter()
+        .filter(|&&(guard, block)| loops(input, guard, block))
+        .map(|&(_, block)| block)
+        .collect();
+    if looped {
+        // The guard keeps to their loop past an obstruction off the patrol.
+        blocks.extend(
+            input
+                .grid
+                .iter()
+                .filter(|&(p, &c)| c == Cell::Empty && visited[p].is_empty())
+                .map(|(p, _)| p),
+        );
+    }
+    blocks
+}
+
+/// Whether the guard, turning in front of a new obstruction at `block`, ends up
+/// walking in a loop.
+fn loops(input: &Input, guard: (Pos, Direction), block: Pos) -> bool {
+    let mut visited = Grid::new(input.grid.width(), input.grid.height(), Directions::empty());
+    let mut guard = (guard.0, guard.1.clockwise());
+    visited[guard.0] = guard.1.into();
+    while let Some(next) = input.grid.step(guard.0, guard.1.delta()) {
+        if input.grid[next] == Cell::Used || next == block {
+            guard.1 = guard.1.clockwise();
+        } else {
+            guard.0 = next;
+        }
+        let seen = &mut visited[guard.0];
+        if seen.contains(guard.1.into()) {
+            debug!(obstruction = %block, "loop detected");
+            return true;
+        }
+        seen.insert(guard.1.into());
+    }
+    false
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
 
-        if let Cell::Used = input.grid[next_pos.0][next_pos.1] {
-            guard = (guard.0, guard.1, guard.2.rotate());
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+/// The guard patrolling, part 2 also showing the obstructions they walked past.
+impl Animate for Day6 {
+    fn frames<'a>(input: &'a Input, _: &'a (), part: u8) -> Box<dyn Iterator<Item = Frame> + 'a> {
+        let obstructions = if part == 2 {
+            obstructions(input)
         } else {
-            let new_block = next_pos;
-
-            if visited[new_block.0][new_block.1].is_empty() {
-                let mut new_guard = (guard.0, guard.1, guard.2.rotate());
-                let mut new_visited = visited.clone();
-                new_visited[new_guard.0][new_guard.1] = new_guard.2.into();
-                new_visited[guard.0][guard.1] = guard.2.into();
-                loop {
-                    let new_guard_pos = (
-                        new_guard.0.wrapping_add_signed(new_guard.2.get_delta().0),
-                        new_guard.1.wrapping_add_signed(new_guard.2.get_delta().1),
-                    );
-                    if new_guard_pos.0 >= input.h || new_guard_pos.1 >= input.w {
-                        break;
-                    }
-                    if input.grid[new_guard_pos.0][new_guard_pos.1] == Cell::Used
-                        || new_guard_pos == new_block
-                    {
-                        new_guard = (new_guard.0, new_guard.1, new_guard.2.rotate());
-                    } else {
-                        new_guard = (new_guard_pos.0, new_guard_pos.1, new_guard.2);
-                    }
-                    if new_visited[new_guard.0][new_guard.1].contains(new_guard.2.into()) {
-                        res.insert(new_block);
-                        break;
-                    }
-                    new_visited[new_guard.0][new_guard.1].insert(new_guard.2.into());
+            HashSet::default()
+        };
+        let mut walked = input.grid.map(|_| false);
+        let mut seen = HashSet::default();
+        Box::new(
+            std::iter::successors(Some(input.guard_start), |&g| patrol_step(&input.grid, g))
+                .take_while(move |&g| seen.insert(g))
+                .map(move |(p, d)| {
+                    walked[p] = true;
+                    let trail = walked.iter().filter(|&(_, &w)| w).map(|(p, _)| p);
+                    Frame::new(&input.grid, |c| match c {
+                        Cell::Used => Glyph::new('#', Rgb::GREY),
+                        _ => Glyph::new('.', Rgb::DARK),
+                    })
+                    .path(trail, Glyph::new('X', Rgb::YELLOW))
+                    .path(
+                        obstructions.iter().copied().filter(|&o| walked[o]),
+                        Glyph::new('O', Rgb::GREEN),
+                    )
+                    .path([p], Glyph::new(d.arrow(), Rgb::RED))
+                }),
+        )
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Tiles walked by the guard, and whether it ends up in a loop.
+    fn patrol(input: &Input, block: Option<Pos>) -> (usize, bool) {
+        let mut seen = HashSet::default();
+        let (mut p, mut d) = input.guard_start;
+        while seen.insert((p, d)) {
+            match input.grid.step(p, d.delta()) {
+                None => return (seen.iter().map(|(p, _)| p).unique().count(), false),
+                Some(next) if input.grid[next] == Cell::Used || Some(next) == block => {
+                    d = d.clockwise();
                 }
+                Some(next) => p = next,
             }
+        }
+        (seen.iter().map(|(p, _)| p).unique().count(), true)
+    }
 
-            guard = (next_pos.0, next_pos.1, guard.2);
+    impl Reference for Day6 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            patrol(input, None).0.into()
         }
 
-        if visited[guard.0][guard.1].contains(guard.2.into()) {
-            break;
+        /// Puts an obstruction on every empty tile in turn.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            input
+                .grid
+                .iter()
+                .filter(|&(p, c)| *c == Cell::Empty && patrol(input, Some(p)).1)
+                .count()
+                .into()
         }
-        visited[guard.0][guard.1] = guard.2.into();
     }
-    res.len()
 }
 
 #[cfg(test)]
@@ -210,10 +266,27 @@ mod tests {
 ......#...";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 41)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(41))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 6)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(6))
+    }
+
+    #[test]
+    fn looping_guard() {
+        let input = parse(".##...#\n......#\n#^.....\n.....##\n.##..#.\n.......\n....#..").unwrap();
+        assert_eq!(part1(&input), Answer::Int(10));
+        assert_eq!(part2(&input), Answer::Int(28));
+    }
+    #[test]
+    fn bad_input() {
+        let err = parse("..#\n.x^\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 2, "\"x\""));
+        assert_eq!(parse("..#\n...").unwrap_err().expected, "a guard");
+        let err = parse("^.#\n..>\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 3, "\">\""));
+        assert_eq!(err.expected, "a single guard");
     }
 }
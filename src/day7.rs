@@ -1,5 +1,13 @@
+use nom::character::complete::{char, space0};
+use nom::combinator::map;
+use nom::sequence::{pair, separated_pair};
 use rustc_hash::FxHashSet as HashSet;
-use std::str::pattern::Pattern;
+
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::par::*;
+use crate::parsing::{PResult, fail, lines, number, spaced};
+use crate::solution::Solution;
 #[derive(Debug)]
 pub struct Equation {
     test: usize,
@@ -12,97 +20,176 @@ pub struct Input {
 }
 
 #[aoc_generator(day7)]
-pub fn parse(input: &str) -> Input {
-    Input {
-        equations: input
-            .lines()
-            .map(|l| {
-                let (test, equation) = l.split_once(": ").unwrap();
-                Equation {
-                    test: test.parse().unwrap(),
-                    equation: equation
-                        .split_whitespace()
-                        .map(|s| s.parse().unwrap())
-                        .collect(),
-                }
-            })
-            .collect(),
-    }
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    // A 0 operand would let `*` reach 0 from any value.
+    let operand = |s| -> PResult<'_, usize> {
+        match number("an operand")(s)? {
+            (_, 0) => Err(fail(s, "a positive operand")),
+            ok => Ok(ok),
+        }
+    };
+    let equation = map(
+        separated_pair(
+            number("a test value"),
+            pair(char(':'), space0),
+            spaced(operand),
+        ),
+        |(test, equation)| Equation { test, equation },
+    );
+    Ok(Input {
+        equations: Source::new(7, input).run(lines(equation))?,
+    })
 }
 
 #[aoc(day7, part1)]
-pub fn part1(input: &Input) -> usize {
-    input.equations.iter().fold(0, |acc, e| {
-        let mut hs = HashSet::default();
-        hs.insert(e.test);
-        e.equation.iter().rev().for_each(|&n| {
-            let add = hs
-                .iter()
-                .filter_map(|d| if n <= *d { Some(d - n) } else { None })
-                .collect::<HashSet<_>>();
-            let mul = hs
-                .iter()
-                .filter_map(|d| {
-                    if d.rem_euclid(n) == 0 {
-                        Some(d / n)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<HashSet<_>>();
+pub fn part1(input: &Input) -> Answer {
+    input
+        .equations
+        .par_iter()
+        .map(|e| {
+            let (first, rest) = e.equation.split_first().unwrap();
+            let mut hs = HashSet::default();
+            hs.insert(e.test);
+            rest.iter().rev().for_each(|&n| {
+                let add = hs
+                    .iter()
+                    .filter_map(|d| if n <= *d { Some(d - n) } else { None })
+                    .collect::<HashSet<_>>();
+                let mul = hs
+                    .iter()
+                    .filter_map(|d| {
+                        if d.rem_euclid(n) == 0 {
+                            Some(d / n)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<HashSet<_>>();
 
-            hs = add.union(&mul).copied().collect();
-        });
-        acc + hs.contains(&0).then(|| e.test).unwrap_or(0)
-    })
+                hs = add.union(&mul).copied().collect();
+            });
+            if hs.contains(first) { e.test } else { 0 }
+        })
+        .sum::<usize>()
+        .into()
+}
+
+/// `d` without the trailing digits of `n`, if it ends with them.
+fn strip_digits(d: usize, n: usize) -> Option<usize> {
+    let digits = n.checked_ilog10().unwrap_or(0) + 1;
+    match 10usize.checked_pow(digits) {
+        Some(pow) => (d % pow == n).then_some(d / pow),
+        None => (d == n).then_some(0),
+    }
 }
 
 #[aoc(day7, part2)]
-pub fn part2(input: &Input) -> usize {
-    input.equations.iter().fold(0, |acc, e| {
-        let mut hs = HashSet::default();
-        hs.insert(e.test);
-        e.equation.iter().rev().for_each(|&n| {
-            let add = hs
-                .iter()
-                .filter_map(|d| if n <= *d { Some(d - n) } else { None })
-                .collect::<HashSet<_>>();
-            let mul = hs
-                .iter()
-                .filter_map(|d| {
-                    if d.rem_euclid(n) == 0 {
-                        Some(d / n)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<HashSet<_>>();
-            let concat = hs
-                .iter()
-                .filter_map(|d| {
-                    if n.to_string().is_suffix_of(d.to_string().as_str()) {
-                        Some(
-                            d.to_string()
-                                .strip_suffix(n.to_string().as_str())
-                                .unwrap()
-                                .parse()
-                                .unwrap_or(0),
-                        )
-                    } else {
-                        None
-                    }
+pub fn part2(input: &Input) -> Answer {
+    input
+        .equations
+        .par_iter()
+        .map(|e| {
+            let (first, rest) = e.equation.split_first().unwrap();
+            let mut hs = HashSet::default();
+            hs.insert(e.test);
+            rest.iter().rev().for_each(|&n| {
+                let add = hs
+                    .iter()
+                    .filter_map(|d| if n <= *d { Some(d - n) } else { None })
+                    .collect::<HashSet<_>>();
+                let mul = hs
+                    .iter()
+                    .filter_map(|d| {
+                        if d.rem_euclid(n) == 0 {
+                            Some(d / n)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<HashSet<_>>();
+                let concat = hs
+                    .iter()
+                    .filter_map(|&d| strip_digits(d, n))
+                    .collect::<HashSet<_>>();
+
+                hs = add.union(&mul).copied().collect();
+                hs = hs.union(&concat).copied().collect();
+            });
+            if hs.contains(first) { e.test } else { 0 }
+        })
+        .sum::<usize>()
+        .into()
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Sum of the test values reached by some combination of the first `ops`
+    /// operators among `+`, `*` and `||`, evaluated left to right.
+    fn calibration(input: &Input, ops: usize) -> Answer {
+        input
+            .equations
+            .iter()
+            .filter(|e| {
+                let (first, rest) = e.equation.split_first().unwrap();
+                (0..ops.pow(rest.len() as u32)).any(|combination| {
+                    let mut combination = combination;
+                    let value = rest.iter().fold(*first, |acc, &n| {
+                        let op = combination % ops;
+                        combination /= ops;
+                        match op {
+                            0 => acc + n,
+                            1 => acc * n,
+                            _ => format!("{acc}{n}").parse().unwrap(),
+                        }
+                    });
+                    value == e.test
                 })
-                .collect::<HashSet<_>>();
+            })
+            .map(|e| e.test)
+            .sum::<usize>()
+            .into()
+    }
 
-            hs = add.union(&mul).copied().collect();
-            hs = hs.union(&concat).copied().collect();
-        });
-        acc + hs.contains(&0).then(|| e.test).unwrap_or(0)
-    })
+    impl Reference for Day7 {
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            calibration(input, 2)
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            calibration(input, 3)
+        }
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
     const INPUT: &str = "190: 10 19
 3267: 81 40 27
 83: 17 5
@@ -114,10 +201,36 @@ mod tests {
 292: 11 6 16 20";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 3749)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(3749))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 11387)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(11387))
+    }
+
+    #[test]
+    fn first_operand() {
+        // 0 * 3 + 5 would reach 5, but nothing comes before the first operand.
+        let input = parse("5: 3 5").unwrap();
+        assert_eq!(part1(&input), Answer::Int(0));
+        assert_eq!(part2(&input), Answer::Int(0));
+    }
+
+    #[test]
+    fn zero_operand() {
+        let err = parse("0: 0 0").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 4));
+        assert_eq!(err.expected, "a positive operand");
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn part1_below_part2(seed: u64, size in 1usize..20) {
+            let input = parse(&generate::day7(&mut rng(seed), size)).unwrap();
+            prop_assert!(part1(&input).int() <= part2(&input).int());
+        }
     }
 }
@@ -1,54 +1,49 @@
 use itertools::Itertools;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::pos::{Bounds, Pos, Vec2};
+use crate::solution::Solution;
+
 #[derive(Default, Debug, Clone)]
 pub struct Input {
-    w: usize,
-    h: usize,
-    frequencies: HashMap<char, HashSet<(usize, usize)>>,
+    bounds: Bounds,
+    frequencies: HashMap<char, HashSet<Pos>>,
 }
 
 #[aoc_generator(day8)]
-pub fn parse(input: &str) -> Input {
-    let mut frequencies: HashMap<char, HashSet<(usize, usize)>> = HashMap::default();
-    let grid: Vec<Vec<char>> = input
-        .lines()
-        .enumerate()
-        .map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .map(|(j, c)| {
-                    if c != '.' {
-                        frequencies.entry(c).or_default().insert((i, j));
-                    }
-                    c
-                })
-                .collect()
-        })
-        .collect();
-    Input {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut frequencies: HashMap<char, HashSet<Pos>> = HashMap::default();
+    let grid = Source::new(8, input).grid(input, "'.' or an antenna", |p, c| {
+        if c.is_ascii_alphanumeric() {
+            frequencies.entry(c).or_default().insert(p);
+        }
+        (c == '.' || c.is_ascii_alphanumeric()).then_some(c)
+    })?;
+    Ok(Input {
         frequencies,
-        w: grid[0].len(),
-        h: grid.len(),
-    }
+        bounds: grid.bounds(),
+    })
 }
 
 #[aoc(day8, part1)]
-pub fn part1(input: &Input) -> usize {
+pub fn part1(input: &Input) -> Answer {
     input
         .frequencies
         .iter()
         .fold(HashSet::default(), |mut acc, fr| {
             acc.extend(fr.1.iter().tuple_combinations().fold(
                 HashSet::default(),
-                |mut acc, (a, b)| {
-                    let new_a = ((2 * b.0).wrapping_sub(a.0), (2 * b.1).wrapping_sub(a.1));
-                    let new_b = ((2 * a.0).wrapping_sub(b.0), (2 * a.1).wrapping_sub(b.1));
-                    if new_a.0 < input.h && new_a.1 < input.w {
-                        acc.insert(new_a);
-                    }
-                    if new_b.0 < input.h && new_b.1 < input.w {
-                        acc.insert(new_b);
+                |mut acc, (&a, &b)| {
+                    let d = b - a;
+                    acc.extend(b.step(d, input.bounds));
+                    acc.extend(a.step(-d, input.bounds));
+                    // One antenna is also twice as far at the thirds of the way.
+                    if d.x % 3 == 0 && d.y % 3 == 0 {
+                        let third = Vec2::new(d.x / 3, d.y / 3);
+                        acc.extend(a.step(third, input.bounds));
+                        acc.extend(b.step(-third, input.bounds));
                     }
                     acc
                 },
@@ -56,41 +51,34 @@ pub fn part1(input: &Input) -> usize {
             acc
         })
         .len()
+        .into()
 }
+
+/// Shortest step between tiles in line with `v`.
+fn unit(v: Vec2) -> Vec2 {
+    let (mut a, mut b) = (v.x.unsigned_abs(), v.y.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    Vec2::new(v.x / a as isize, v.y / a as isize)
+}
+
 #[aoc(day8, part2)]
-pub fn part2(input: &Input) -> usize {
+pub fn part2(input: &Input) -> Answer {
     input
         .frequencies
         .iter()
         .fold(HashSet::default(), |mut acc, fr| {
             acc.extend(fr.1.iter().tuple_combinations().fold(
                 HashSet::default(),
-                |mut acc, (a, b)| {
-                    let d1 = (a.0 as isize - b.0 as isize, a.1 as isize - b.1 as isize);
-                    let d2 = (b.0 as isize - a.0 as isize, b.1 as isize - a.1 as isize);
-
-                    let mut next_a = *b;
-                    let mut next_b = *a;
-                    loop {
-                        if next_a.0 >= input.h || next_a.1 >= input.w {
-                            break;
+                |mut acc, (&a, &b)| {
+                    let d = unit(b - a);
+                    for d in [d, -d] {
+                        let mut next = Some(a);
+                        while let Some(p) = next {
+                            acc.insert(p);
+                            next = p.step(d, input.bounds);
                         }
-                        acc.insert(next_a);
-                        next_a = (
-                            next_a.0.wrapping_sub_signed(d1.0),
-                            next_a.1.wrapping_sub_signed(d1.1),
-                        );
-                    }
-
-                    loop {
-                        if next_b.0 >= input.h || next_b.1 >= input.w {
-                            break;
-                        }
-                        acc.insert(next_b);
-                        next_b = (
-                            next_b.0.wrapping_sub_signed(d2.0),
-                            next_b.1.wrapping_sub_signed(d2.1),
-                        );
                     }
                     acc
                 },
@@ -98,10 +86,75 @@ pub fn part2(input: &Input) -> usize {
             acc
         })
         .len()
+        .into()
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Parsed = Input;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
 }
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    /// Tiles for which `antinode` holds with some pair of same frequency antennas.
+    fn antinodes(input: &Input, antinode: impl Fn(Vec2, Vec2) -> bool) -> Answer {
+        (0..input.bounds.w)
+            .cartesian_product(0..input.bounds.h)
+            .filter(|&(x, y)| {
+                let p = Pos::new(x, y);
+                input.frequencies.values().any(|antennas| {
+                    antennas
+                        .iter()
+                        .permutations(2)
+                        .any(|pair| antinode(p - *pair[0], p - *pair[1]))
+                })
+            })
+            .count()
+            .into()
+    }
+
+    fn in_line(a: Vec2, b: Vec2) -> bool {
+        a.x * b.y == a.y * b.x
+    }
+
+    impl Reference for Day8 {
+        /// In line with two antennas, one twice as far as the other.
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            antinodes(input, |a, b| {
+                in_line(a, b) && a.manhattan() == 2 * b.manhattan()
+            })
+        }
+
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            antinodes(input, in_line)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use proptest::prelude::*;
     const INPUT: &str = "............
 ........0...
 .....0......
@@ -117,10 +170,36 @@ mod tests {
 ";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 14)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(14))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 34)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(34))
+    }
+
+    #[test]
+    fn thirds() {
+        let input = "a.........\n..........\n..........\n...a......\n";
+        let input = format!("{input}{}", "..........\n".repeat(6));
+        // (1, 1) and (2, 2) besides (6, 6).
+        assert_eq!(part1(&parse(&input).unwrap()), Answer::Int(3))
+    }
+
+    #[test]
+    fn between_multiples() {
+        let input = "a....\n.....\n.....\n.....\n..a..\n";
+        // (1, 2) lies between the antennas at (0, 0) and (2, 4).
+        assert_eq!(part2(&parse(input).unwrap()), Answer::Int(3))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn part1_below_part2(seed: u64, size in 1usize..30) {
+            let input = parse(&generate::day8(&mut rng(seed), size)).unwrap();
+            prop_assert!(part1(&input).int() <= part2(&input).int());
+        }
     }
 }
@@ -1,3 +1,8 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Source};
+use crate::parsing::row;
+use crate::solution::Solution;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Cell {
     File(usize, usize),
@@ -5,21 +10,22 @@ pub enum Cell {
 }
 
 #[aoc_generator(day9)]
-pub fn parse(input: &str) -> Vec<Cell> {
-    input
-        .chars()
+pub fn parse(input: &str) -> Result<Vec<Cell>, ParseError> {
+    let sizes = Source::new(9, input).run(row("a digit", |c| c.to_digit(10)))?;
+    Ok(sizes
+        .into_iter()
         .enumerate()
-        .map(|(i, c)| {
+        .map(|(i, size)| {
             if i % 2 == 0 {
-                Cell::File(i / 2, c.to_digit(10).unwrap() as usize)
+                Cell::File(i / 2, size as usize)
             } else {
-                Cell::Free(c.to_digit(10).unwrap() as usize)
+                Cell::Free(size as usize)
             }
         })
-        .collect()
+        .collect())
 }
 #[aoc(day9, part1)]
-pub fn part1(input: &[Cell]) -> usize {
+pub fn part1(input: &[Cell]) -> Answer {
     let mut input = input.to_vec();
     let mut i = 0;
     loop {
@@ -69,12 +75,20 @@ pub fn part1(input: &[Cell]) -> usize {
         }
         Cell::Free(_) => unreachable!(),
     });
-    res
+    res.into()
 }
 #[aoc(day9, part2)]
-pub fn part2(input: &[Cell]) -> usize {
-    let mut input = input.to_vec();
-    let mut i = input.len() - 1;
+pub fn part2(cells: &[Cell]) -> Answer {
+    // Empty files take no room, the free space around them is one span.
+    let mut input: Vec<Cell> = Vec::with_capacity(cells.len());
+    for &c in cells {
+        match (input.last_mut(), c) {
+            (_, Cell::File(_, 0)) => (),
+            (Some(Cell::Free(free)), Cell::Free(size)) => *free += size,
+            _ => input.push(c),
+        }
+    }
+    let mut i = input.len().saturating_sub(1);
 
     loop {
         if i == 0 {
@@ -124,18 +138,128 @@ pub fn part2(input: &[Cell]) -> usize {
         }
         Cell::Free(size) => i += size,
     });
-    res
+    res.into()
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Parsed = Vec<Cell>;
+    type Params = ();
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Parsed, _: &()) -> Answer {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Parsed, _: &()) -> Answer {
+        part2(input)
+    }
+}
+
+#[cfg(any(test, feature = "reference"))]
+mod reference {
+    use super::*;
+    use crate::reference::Reference;
+
+    #[test]
+    fn empty_files() {
+        // The free spans around the empty file 2 together fit file 3.
+        assert_eq!(part2(&parse("12023").unwrap()), Answer::Int(12))
+    }
+
+    /// One entry per block, the id of the file it holds.
+    fn blocks(input: &[Cell]) -> Vec<Option<usize>> {
+        input
+            .iter()
+            .flat_map(|&c| match c {
+                Cell::File(id, size) => vec![Some(id); size],
+                Cell::Free(size) => vec![None; size],
+            })
+            .collect()
+    }
+
+    fn checksum(blocks: &[Option<usize>]) -> Answer {
+        let sum: usize = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| i * b.unwrap_or(0))
+            .sum();
+        sum.into()
+    }
+
+    impl Reference for Day9 {
+        /// Moves the last block to the first free one until none is left of it.
+        fn part1(input: &Self::Parsed, _: &()) -> Answer {
+            let mut blocks = blocks(input);
+            let mut free = 0;
+            while let Some(last) = blocks.pop() {
+                while free < blocks.len() && blocks[free].is_some() {
+                    free += 1;
+                }
+                if free < blocks.len() {
+                    blocks[free] = last;
+                } else {
+                    blocks.push(last);
+                    break;
+                }
+            }
+            checksum(&blocks)
+        }
+
+        /// Moves each file to the first free span fitting it, block by block.
+        fn part2(input: &Self::Parsed, _: &()) -> Answer {
+            let mut blocks = blocks(input);
+            let files = input.iter().filter(|c| matches!(c, Cell::File(..))).count();
+            for id in (0..files).rev() {
+                let Some(start) = blocks.iter().position(|&b| b == Some(id)) else {
+                    continue;
+                };
+                let size = blocks[start..]
+                    .iter()
+                    .take_while(|&&b| b == Some(id))
+                    .count();
+                let span = (0..start).find(|&i| blocks[i..i + size].iter().all(Option::is_none));
+                if let Some(i) = span.filter(|&i| i + size <= start) {
+                    blocks[i..i + size].fill(Some(id));
+                    blocks[start..start + size].fill(None);
+                }
+            }
+            checksum(&blocks)
+        }
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, rng};
+    use crate::reference::Reference;
+    use proptest::prelude::*;
     const INPUT: &str = "2333133121414131402";
     #[test]
     fn example1() {
-        assert_eq!(part1(&parse(INPUT)), 1928)
+        assert_eq!(part1(&parse(INPUT).unwrap()), Answer::Int(1928))
     }
+
     #[test]
     fn example2() {
-        assert_eq!(part2(&parse(INPUT)), 2858)
+        assert_eq!(part2(&parse(INPUT).unwrap()), Answer::Int(2858))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn naive(seed: u64, size in 1usize..40) {
+            let input = parse(&generate::day9(&mut rng(seed), size)).unwrap();
+            prop_assert_eq!(part1(&input), <Day9 as Reference>::part1(&input, &()));
+            prop_assert_eq!(part2(&input), <Day9 as Reference>::part2(&input, &()));
+        }
     }
 }
@@ -0,0 +1,155 @@
+use bitflags::bitflags;
+
+use crate::pos::Vec2;
+
+/// One of the four grid headings, with `Up` towards row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+bitflags! {
+    /// Set of headings, e.g. the ones a cell was already crossed with.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Directions: u8 {
+        const UP = 1;
+        const DOWN = 1 << 1;
+        const LEFT = 1 << 2;
+        const RIGHT = 1 << 3;
+    }
+}
+
+impl From<Direction> for Directions {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Directions::UP,
+            Direction::Down => Directions::DOWN,
+            Direction::Left => Directions::LEFT,
+            Direction::Right => Directions::RIGHT,
+        }
+    }
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        Self::ALL.into_iter()
+    }
+
+    /// Parses `^`, `v`, `<` and `>`.
+    pub fn from_arrow(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            '>' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Parses `N`, `S`, `W` and `E`.
+    pub fn from_compass(c: char) -> Option<Self> {
+        match c {
+            'N' => Some(Direction::Up),
+            'S' => Some(Direction::Down),
+            'W' => Some(Direction::Left),
+            'E' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+
+    /// Delta of a single step.
+    pub fn delta(&self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::UP,
+            Direction::Down => Vec2::DOWN,
+            Direction::Left => Vec2::LEFT,
+            Direction::Right => Vec2::RIGHT,
+        }
+    }
+
+    pub fn clockwise(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn counter_clockwise(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_arrow(c)
+            .or_else(|| Self::from_compass(c))
+            .ok_or(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotations() {
+        for d in Direction::iter() {
+            assert_eq!(d.clockwise().counter_clockwise(), d);
+            assert_eq!(d.clockwise().clockwise(), d.reverse());
+            assert_eq!(Direction::from_arrow(d.arrow()), Some(d));
+            assert_eq!(d.reverse().delta(), -d.delta());
+            assert_eq!(d.clockwise().delta(), d.delta().clockwise());
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Direction::try_from('E'), Ok(Direction::Right));
+        assert_eq!(Direction::try_from('v'), Ok(Direction::Down));
+        assert_eq!(Direction::try_from('x'), Err('x'));
+        let set = Directions::from(Direction::Up) | Direction::Left.into();
+        assert!(set.contains(Direction::Left.into()));
+        assert!(!set.contains(Direction::Down.into()));
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::Parser;
+use nom::character::complete::multispace0;
+use nom::combinator::all_consuming;
+use nom::sequence::terminated;
+
+use crate::grid::Grid;
+use crate::parsing;
+use crate::pos::Pos;
+
+/// Malformed puzzle input, located by 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} input, line {}, column {}: expected {}, found {}",
+            self.day, self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Invalid override of a puzzle parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamError {
+    pub day: u8,
+    pub message: String,
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {} parameters: {}", self.day, self.message)
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Anything preventing a day from being solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Param(ParamError),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => e.fmt(f),
+            SolveError::Param(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> Self {
+        SolveError::Parse(e)
+    }
+}
+
+impl From<ParamError> for SolveError {
+    fn from(e: ParamError) -> Self {
+        SolveError::Param(e)
+    }
+}
+
+/// Puzzle input of a given day, used to turn sub-slices into positioned errors.
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'a> {
+    day: u8,
+    input: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(day: u8, input: &'a str) -> Self {
+        Source { day, input }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// Byte offset of `at`, which should be a sub-slice of the input.
+    fn offset(&self, at: &str) -> usize {
+        let start = self.input.as_ptr() as usize;
+        (at.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|&o| o <= self.input.len())
+            .unwrap_or(self.input.len())
+    }
+
+    /// Error pointing at the beginning of `at`.
+    pub fn error(&self, at: &str, expected: impl Into<String>) -> ParseError {
+        let offset = self.offset(at);
+        let before = &self.input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        let rest = &self.input[offset..];
+        let token = at.lines().next().unwrap_or("");
+        let found = if !token.is_empty() {
+            format!("{:?}", token.chars().take(20).collect::<String>())
+        } else if rest.is_empty() {
+            "end of input".to_owned()
+        } else if rest.starts_with(['\n', '\r']) {
+            "end of line".to_owned()
+        } else {
+            format!("{:?}", rest.chars().next().unwrap())
+        };
+        ParseError {
+            day: self.day,
+            line,
+            column,
+            expected: expected.into(),
+            found,
+        }
+    }
+
+    /// Error pointing at the end of the input.
+    pub fn eof(&self, expected: impl Into<String>) -> ParseError {
+        self.error(&self.input[self.input.len()..], expected)
+    }
+
+    pub fn split_once<'b>(&self, s: &'b str, sep: &str) -> Result<(&'b str, &'b str), ParseError> {
+        s.split_once(sep)
+            .ok_or_else(|| self.error(&s[s.len()..], format!("{sep:?}")))
+    }
+
+    pub fn strip_prefix<'b>(&self, s: &'b str, prefix: &str) -> Result<&'b str, ParseError> {
+        s.strip_prefix(prefix)
+            .ok_or_else(|| self.error(s, format!("{prefix:?}")))
+    }
+
+    pub fn parse<T: FromStr>(&self, s: &str, expected: &str) -> Result<T, ParseError> {
+        s.parse().map_err(|_| self.error(s, expected))
+    }
+
+    /// Runs `parser` over the whole input, ignoring trailing whitespace.
+    pub fn run<T>(
+        &self,
+        parser: impl Parser<&'a str, T, parsing::Error<'a>>,
+    ) -> Result<T, ParseError> {
+        self.complete(self.input, parser)
+    }
+
+    /// Parses a rectangular character map, `f` rejecting a cell by returning `None`.
+    pub fn grid<T>(
+        &self,
+        s: &'a str,
+        expected: &'static str,
+        f: impl FnMut(Pos, char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        self.complete(s, parsing::char_map(expected, f))
+    }
+
+    fn complete<T>(
+        &self,
+        s: &'a str,
+        parser: impl Parser<&'a str, T, parsing::Error<'a>>,
+    ) -> Result<T, ParseError> {
+        all_consuming(terminated(parser, multispace0))(s)
+            .map(|(_, v)| v)
+            .map_err(|e| match e {
+                nom::Err::Error(e) | nom::Err::Failure(e) => self.error(e.input, e.expectation()),
+                nom::Err::Incomplete(_) => self.eof("more input"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let input = "12 34\n56 x8\n";
+        let src = Source::new(1, input);
+        let err = src.parse::<usize>(&input[9..11], "a number").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.found, "\"x8\"");
+        assert_eq!(
+            err.to_string(),
+            "day 1 input, line 2, column 4: expected a number, found \"x8\""
+        );
+        let err = src.split_once(&input[..5], ",").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.found.as_str()),
+            (1, 6, "end of line")
+        );
+        assert_eq!(src.eof("more").found, "end of input");
+    }
+
+    #[test]
+    fn grid() {
+        let src = Source::new(4, "ab\nc\n");
+        let err = src
+            .grid(src.input(), "a letter", |_, c| Some(c))
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        let err = src
+            .grid(src.input(), "a or b", |_, c| (c != 'c').then_some(c))
+            .unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 1, "\"c\""));
+    }
+}
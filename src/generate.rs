@@ -0,0 +1,604 @@
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom, index};
+use rand::{Rng, SeedableRng};
+
+use crate::grid::{DIRS4, Grid};
+use crate::pos::{Bounds, Pos, Vec2};
+
+/// Seeded generator of valid inputs for a day, sized for its default parameters.
+pub struct Generator {
+    pub day: u8,
+    /// Parts guaranteed to have an answer on generated inputs.
+    pub parts: &'static [u8],
+    input: fn(&mut StdRng, usize) -> String,
+}
+
+impl Generator {
+    /// Input growing with `size`, always the same for a given `seed`.
+    pub fn input(&self, seed: u64, size: usize) -> String {
+        (self.input)(&mut rng(seed), size)
+    }
+}
+
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+const fn generator(day: u8, input: fn(&mut StdRng, usize) -> String) -> Generator {
+    Generator {
+        day,
+        parts: &[1, 2],
+        input,
+    }
+}
+
+/// Every day's generator, sorted by day number.
+pub static GENERATORS: &[Generator] = &[
+    generator(1, day1),
+    generator(2, day2),
+    generator(3, day3),
+    generator(4, day4),
+    generator(5, day5),
+    generator(6, day6),
+    generator(7, day7),
+    generator(8, day8),
+    generator(9, day9),
+    generator(10, day10),
+    generator(11, day11),
+    generator(12, day12),
+    generator(13, day13),
+    generator(14, |rng, n| day14(rng, n, Bounds::new(101, 103))),
+    generator(15, day15),
+    generator(16, day16),
+    // Part 2 needs a program printing itself.
+    Generator {
+        day: 17,
+        parts: &[1],
+        input: day17,
+    },
+    generator(18, |rng, n| day18(rng, 71, 1024 + n)),
+    generator(19, day19),
+    generator(20, day20),
+    generator(21, day21),
+    generator(22, day22),
+    generator(23, day23),
+    generator(24, day24),
+    generator(25, day25),
+];
+
+pub fn get(day: u8) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|g| g.day == day)
+}
+
+/// `w` by `h` map of the characters given by `f`.
+fn chars(w: usize, h: usize, mut f: impl FnMut(Pos) -> char) -> String {
+    (0..h)
+        .map(|y| (0..w).map(|x| f(Pos::new(x, y))).collect::<String>())
+        .join("\n")
+}
+
+/// `n` pairs of location ids, right ones often repeated.
+pub fn day1(rng: &mut impl Rng, n: usize) -> String {
+    let right: Vec<u32> = (0..n / 2 + 1)
+        .map(|_| rng.random_range(10000..100000))
+        .collect();
+    (0..n.max(1))
+        .map(|_| {
+            let left = rng.random_range(10000..100000);
+            format!("{left}   {}", right.choose(rng).unwrap())
+        })
+        .join("\n")
+}
+
+/// `n` reports, mostly gradual with an occasional bad level.
+pub fn day2(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| {
+            let dir = if rng.random_bool(0.5) { 1 } else { -1 };
+            let mut level: i32 = rng.random_range(40..60);
+            (0..rng.random_range(5..=8))
+                .map(|_| {
+                    level += if rng.random_bool(0.1) {
+                        rng.random_range(-4..=4)
+                    } else {
+                        dir * rng.random_range(1..=3)
+                    };
+                    level
+                })
+                .join(" ")
+        })
+        .join("\n")
+}
+
+/// `n` fragments of corrupted memory, instructions or garbage.
+pub fn day3(rng: &mut impl Rng, n: usize) -> String {
+    const NOISE: &[&str] = &[
+        "do()", "don't()", "mul[", "mul(", ")", ",", " ", "from()", "'", "%", "\n",
+    ];
+    (0..n.max(1))
+        .map(|_| {
+            let (a, b) = (rng.random_range(0..1000), rng.random_range(0..1000));
+            match rng.random_range(0..4) {
+                0 | 1 => format!("mul({a},{b})"),
+                2 => format!("mul({a}, {b})"),
+                _ => NOISE.choose(rng).unwrap().to_string(),
+            }
+        })
+        .collect()
+}
+
+/// `n` sided word search.
+pub fn day4(rng: &mut impl Rng, n: usize) -> String {
+    chars(n.max(1), n.max(1), |_| {
+        *['X', 'M', 'A', 'S'].choose(rng).unwrap()
+    })
+}
+
+/// `n` updates over a total order of pages.
+pub fn day5(rng: &mut impl Rng, n: usize) -> String {
+    let mut pages: Vec<usize> = index::sample(rng, 90, 5 + n.min(44))
+        .into_iter()
+        .map(|p| p + 10)
+        .collect();
+    pages.shuffle(rng);
+    let mut rules: Vec<_> = pages.iter().tuple_combinations().collect();
+    rules.shuffle(rng);
+    let rules = rules.iter().map(|(a, b)| format!("{a}|{b}")).join("\n");
+    let updates = (0..n.max(1))
+        .map(|_| {
+            let len = 2 * rng.random_range(0..=(pages.len().min(23) - 1) / 2) + 1;
+            let mut update = index::sample(rng, pages.len(), len).into_vec();
+            if rng.random_bool(0.5) {
+                update.sort();
+            }
+            update.iter().map(|&i| pages[i]).join(",")
+        })
+        .join("\n");
+    format!("{rules}\n\n{updates}")
+}
+
+/// `n` sided lab with a guard facing up.
+pub fn day6(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(1);
+    let guard = Pos::new(rng.random_range(0..n), rng.random_range(0..n));
+    chars(n, n, |p| match p {
+        _ if p == guard => '^',
+        _ if rng.random_bool(0.1) => '#',
+        _ => '.',
+    })
+}
+
+/// `n` equations. A third of the targets are off by a few and another third
+/// are reached by leaving out the first operand, so most of those two thirds
+/// cannot be made true.
+pub fn day7(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| {
+            let max = if rng.random_bool(0.5) { 10 } else { 100 };
+            let operands: Vec<u64> = (0..rng.random_range(1..=6))
+                .map(|_| rng.random_range(1..max))
+                .collect();
+            let shape = rng.random_range(0..3);
+            let used = if shape == 2 && operands.len() > 1 {
+                &operands[1..]
+            } else {
+                &operands[..]
+            };
+            let mut test = used[0];
+            for &o in &used[1..] {
+                test = match rng.random_range(0..3) {
+                    0 => test + o,
+                    1 => test * o,
+                    _ => test * 10u64.pow(o.ilog10() + 1) + o,
+                };
+            }
+            if shape == 1 {
+                test += rng.random_range(1..10);
+            }
+            format!("{test}: {}", operands.iter().join(" "))
+        })
+        .join("\n")
+}
+
+/// `n` sided map with a few antennas of up to four frequencies.
+pub fn day8(rng: &mut impl Rng, n: usize) -> String {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let n = n.max(1);
+    let mut grid = Grid::new(n, n, '.');
+    for _ in 0..rng.random_range(1..=4) {
+        let frequency = *FREQUENCIES.choose(rng).unwrap() as char;
+        for _ in 0..rng.random_range(2..=4) {
+            grid[Pos::new(rng.random_range(0..n), rng.random_range(0..n))] = frequency;
+        }
+    }
+    grid.to_string()
+}
+
+/// Disk map of `n` digits, with some empty files.
+pub fn day9(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|i| {
+            let min = u32::from(i % 2 == 0 && rng.random_bool(0.9));
+            char::from_digit(rng.random_range(min..10), 10).unwrap()
+        })
+        .collect()
+}
+
+/// `n` sided topographic map, mostly climbing towards the bottom right.
+pub fn day10(rng: &mut impl Rng, n: usize) -> String {
+    chars(n.max(1), n.max(1), |p| {
+        let height = (p.x + p.y + rng.random_range(0..2)) % 10;
+        char::from_digit(height as u32, 10).unwrap()
+    })
+}
+
+/// `n` stones, possibly none.
+pub fn day11(rng: &mut impl Rng, n: usize) -> String {
+    (0..n)
+        .map(|_| match rng.random_range(0..3) {
+            0 => rng.random_range(0..10),
+            1 => rng.random_range(0..1000),
+            _ => rng.random_range(0..1000000),
+        })
+        .join(" ")
+}
+
+/// `n` sided garden of plots spreading from their left or upper neighbour.
+pub fn day12(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(1);
+    let kinds = 1 + (n / 3).min(25) as u8;
+    let mut grid = Grid::new(n, n, 'A');
+    for p in grid.positions() {
+        let around: Vec<Pos> = [Vec2::LEFT, Vec2::UP]
+            .iter()
+            .filter_map(|&d| grid.step(p, d))
+            .collect();
+        grid[p] = match around.choose(rng) {
+            Some(&q) if rng.random_bool(0.6) => grid[q],
+            _ => (b'A' + rng.random_range(0..kinds)) as char,
+        };
+    }
+    grid.to_string()
+}
+
+/// `n` claw machines, two thirds of which can be won within 100 presses.
+pub fn day13(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| {
+            let (a, b) = loop {
+                let a: (i64, i64) = (rng.random_range(10..100), rng.random_range(10..100));
+                let b: (i64, i64) = (rng.random_range(10..100), rng.random_range(10..100));
+                if a.0 * b.1 != a.1 * b.0 {
+                    break (a, b);
+                }
+            };
+            let prize = if rng.random_bool(2.0 / 3.0) {
+                let (i, j) = (rng.random_range(0..=100), rng.random_range(0..=100));
+                (a.0 * i + b.0 * j, a.1 * i + b.1 * j)
+            } else {
+                (rng.random_range(1000..20000), rng.random_range(1000..20000))
+            };
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+                a.0, a.1, b.0, b.1, prize.0, prize.1
+            )
+        })
+        .join("\n\n")
+}
+
+/// Up to `n` robots in a `bounds` sized room, all standing on distinct tiles
+/// at some point.
+pub fn day14(rng: &mut impl Rng, n: usize, bounds: Bounds) -> String {
+    let area = bounds.w * bounds.h;
+    let seconds = rng.random_range(1..area as i64) as isize;
+    index::sample(rng, area, n.clamp(1, area))
+        .into_iter()
+        .map(|i| {
+            let (x, y): (i64, i64) = (rng.random_range(-100..=100), rng.random_range(-100..=100));
+            let v = Vec2::new(x as isize, y as isize);
+            let p = (Pos::from_index(i, bounds.w).to_vec() - v * seconds).wrap(bounds);
+            format!("p={p} v={},{}", v.x, v.y)
+        })
+        .join("\n")
+}
+
+/// `n` sided warehouse, followed by `10 * n` moves.
+pub fn day15(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(3);
+    let robot = Pos::new(rng.random_range(1..n - 1), rng.random_range(1..n - 1));
+    let map = chars(n, n, |p| match p {
+        _ if p.x == 0 || p.y == 0 || p.x == n - 1 || p.y == n - 1 => '#',
+        _ if p == robot => '@',
+        _ => *['.', '.', '.', '.', '.', '.', 'O', 'O', '#']
+            .choose(rng)
+            .unwrap(),
+    });
+    let moves: Vec<char> = (0..10 * n)
+        .map(|_| *['^', 'v', '<', '>'].choose(rng).unwrap())
+        .collect();
+    let moves = moves
+        .chunks(70)
+        .map(|l| l.iter().collect::<String>())
+        .join("\n");
+    format!("{map}\n\n{moves}")
+}
+
+/// `n` sided maze, with a staircase path from the start to the end.
+pub fn day16(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(4);
+    let mut grid = Grid::new(n, n, '.');
+    for p in grid.positions() {
+        if rng.random_bool(0.3) {
+            grid[p] = '#';
+        }
+    }
+    let (start, end) = (Pos::new(1, n - 2), Pos::new(n - 2, 1));
+    let mut p = start;
+    while p != end {
+        grid[p] = '.';
+        p = if p.y == end.y || (p.x < end.x && rng.random_bool(0.5)) {
+            p + Vec2::RIGHT
+        } else {
+            p + Vec2::UP
+        };
+    }
+    for p in grid.positions() {
+        if p.x == 0 || p.y == 0 || p.x == n - 1 || p.y == n - 1 {
+            grid[p] = '#';
+        }
+    }
+    grid[start] = 'S';
+    grid[end] = 'E';
+    grid.to_string()
+}
+
+/// Random program of up to `n` instructions, without jumps but for a last
+/// one back to one of the first numbers, into a loop shifting register A right
+/// on every pass so that it halts.
+pub fn day17(rng: &mut impl Rng, n: usize) -> String {
+    let mut program = Vec::new();
+    for _ in 0..rng.random_range(0..=n.min(16)) {
+        let op = *[0, 1, 2, 4, 5, 6, 7].choose(rng).unwrap();
+        // Combo operand 7 is reserved.
+        let operand = match op {
+            1 | 4 => rng.random_range(0..8),
+            _ => rng.random_range(0..7),
+        };
+        program.extend([op, operand]);
+    }
+    // The jump operand is a 3-bit literal too.
+    let target = rng.random_range(0..=program.len().min(7));
+    program.extend([0, rng.random_range(1..4), 3, target]);
+    format!(
+        "Register A: {}\nRegister B: {}\nRegister C: {}\n\nProgram: {}",
+        rng.random_range(0..1u64 << 48),
+        rng.random_range(0..1u64 << 32),
+        rng.random_range(0..1u64 << 32),
+        program.iter().join(",")
+    )
+}
+
+/// Every byte of a `side` sided memory space, the first `safe` of them (at
+/// most `(side - 1)²`) leaving a path to the exit.
+pub fn day18(rng: &mut impl Rng, side: usize, safe: usize) -> String {
+    let side = side.max(2);
+    let (start, end) = (Pos::new(0, 0), Pos::new(side - 1, side - 1));
+    let mut path = Grid::new(side, side, false);
+    let mut p = start;
+    while p != end {
+        path[p] = true;
+        p = if p.y == end.y || (p.x < end.x && rng.random_bool(0.5)) {
+            p + Vec2::RIGHT
+        } else {
+            p + Vec2::DOWN
+        };
+    }
+    path[end] = true;
+    let (mut safe_bytes, mut on_path): (Vec<Pos>, Vec<Pos>) = path
+        .positions()
+        .filter(|&p| p != start && p != end)
+        .partition(|&p| !path[p]);
+    safe_bytes.shuffle(rng);
+    let (first, rest) = safe_bytes.split_at(safe.min(safe_bytes.len()));
+    let mut rest = rest.to_vec();
+    rest.append(&mut on_path);
+    rest.shuffle(rng);
+    first.iter().chain(&rest).join("\n")
+}
+
+/// Up to `n` towels and `n` designs, half of which can be made.
+pub fn day19(rng: &mut impl Rng, n: usize) -> String {
+    fn stripe(rng: &mut impl Rng, len: usize) -> String {
+        (0..len)
+            .map(|_| *['w', 'u', 'b', 'r', 'g'].choose(rng).unwrap())
+            .collect()
+    }
+    let mut towels: Vec<String> = (0..n.max(1))
+        .map(|_| {
+            let len = rng.random_range(1..=4);
+            stripe(rng, len)
+        })
+        .collect();
+    towels.sort();
+    towels.dedup();
+    let designs = (0..n.max(1))
+        .map(|_| {
+            let len = rng.random_range(4..=16);
+            if rng.random_bool(0.5) {
+                stripe(rng, len)
+            } else {
+                let mut design = String::new();
+                while design.len() < len {
+                    design += towels.choose(rng).unwrap();
+                }
+                design
+            }
+        })
+        .join("\n");
+    towels.shuffle(rng);
+    format!("{}\n\n{designs}", towels.join(", "))
+}
+
+/// Racetrack winding through a `2n + 1` sided map, following a random
+/// self-avoiding walk over its odd tiles.
+pub fn day20(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(2);
+    let side = 2 * n + 1;
+    let mut grid = Grid::new(side, side, '#');
+    let odd = |v: usize| 2 * v + 1;
+    let mut p = Pos::new(odd(rng.random_range(0..n)), odd(rng.random_range(0..n)));
+    let bounds = Bounds::new(side - 1, side - 1);
+    grid[p] = 'S';
+    loop {
+        let free: Vec<Vec2> = DIRS4
+            .iter()
+            .copied()
+            .filter(|&d| p.step(d * 2, bounds).is_some_and(|q| grid[q] == '#'))
+            .collect();
+        let Some(&d) = free.choose(rng) else {
+            break;
+        };
+        grid[p + d] = '.';
+        p += d * 2;
+        grid[p] = '.';
+    }
+    grid[p] = 'E';
+    grid.to_string()
+}
+
+/// `n` codes of three digits.
+pub fn day21(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| format!("{:03}A", rng.random_range(0..1000)))
+        .join("\n")
+}
+
+/// `n` initial secrets.
+pub fn day22(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| rng.random_range(1u32..1 << 24))
+        .join("\n")
+}
+
+/// `n` computers, a third of them forming the only largest LAN party.
+pub fn day23(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.max(3);
+    let names: Vec<String> = index::sample(rng, 26 * 26, n)
+        .into_iter()
+        .map(|i| {
+            format!(
+                "{}{}",
+                (b'a' + (i / 26) as u8) as char,
+                (b'a' + (i % 26) as u8) as char
+            )
+        })
+        .collect();
+    let party = index::sample(rng, n, (n / 3).max(3)).into_vec();
+    let mut edges: Vec<(usize, usize)> = party.iter().copied().tuple_combinations().collect();
+    for guest in (0..n).filter(|i| !party.contains(i)) {
+        // Knowing all but two of the party, a guest joins smaller cliques only.
+        let known = rng.random_range(0..party.len() - 1);
+        edges.extend(party.choose_multiple(rng, known).map(|&host| (guest, host)));
+    }
+    for edge in &mut edges {
+        if rng.random_bool(0.5) {
+            *edge = (edge.1, edge.0);
+        }
+    }
+    edges.shuffle(rng);
+    edges
+        .iter()
+        .map(|&(a, b)| format!("{}-{}", names[a], names[b]))
+        .join("\n")
+}
+
+/// Ripple-carry adder of `n` bits with random inputs and wire names.
+pub fn day24(rng: &mut impl Rng, n: usize) -> String {
+    let n = n.clamp(2, 44);
+    let letter = |k: usize| (b'a' + (k % 26) as u8) as char;
+    // Names start before `x` so they never clash with the buses.
+    let mut names = index::sample(rng, 23 * 26 * 26, 5 * n)
+        .into_iter()
+        .map(|i| format!("{}{}{}", letter(i / 676), letter(i / 26), letter(i)));
+    let mut name = || names.next().unwrap();
+    let mut gates = vec![
+        ("x00".to_owned(), "XOR", "y00".to_owned(), "z00".to_owned()),
+        ("x00".to_owned(), "AND", "y00".to_owned(), name()),
+    ];
+    for i in 1..n {
+        let carry = gates.last().unwrap().3.clone();
+        let (xor, and, through) = (name(), name(), name());
+        let out = if i == n - 1 {
+            format!("z{n:02}")
+        } else {
+            name()
+        };
+        gates.extend([
+            (format!("x{i:02}"), "XOR", format!("y{i:02}"), xor.clone()),
+            (format!("x{i:02}"), "AND", format!("y{i:02}"), and.clone()),
+            (xor.clone(), "XOR", carry.clone(), format!("z{i:02}")),
+            (carry, "AND", xor, through.clone()),
+            (and, "OR", through, out),
+        ]);
+    }
+    gates.shuffle(rng);
+    let values = (0..n)
+        .flat_map(|i| [format!("x{i:02}"), format!("y{i:02}")])
+        .map(|w| format!("{w}: {}", rng.random_range(0..2)))
+        .join("\n");
+    let gates = gates
+        .into_iter()
+        .map(|(a, op, b, out)| {
+            let (a, b) = if rng.random_bool(0.5) { (a, b) } else { (b, a) };
+            format!("{a} {op} {b} -> {out}")
+        })
+        .join("\n");
+    format!("{values}\n\n{gates}")
+}
+
+/// `n` schematics of locks and keys.
+pub fn day25(rng: &mut impl Rng, n: usize) -> String {
+    (0..n.max(1))
+        .map(|_| {
+            let lock = rng.random_bool(0.5);
+            let heights: Vec<usize> = (0..5).map(|_| rng.random_range(0..=5)).collect();
+            chars(5, 7, |p| {
+                let pin = if lock { p.y } else { 6 - p.y };
+                if pin <= heights[p.x] || pin == 0 {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+        })
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+    use proptest::prelude::*;
+
+    #[test]
+    fn deterministic() {
+        for g in GENERATORS {
+            assert_eq!(g.input(7, 10), g.input(7, 10), "day {}", g.day);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn solvable(seed: u64, size in 0usize..20) {
+            for g in GENERATORS {
+                let input = g.input(seed, size);
+                let day = registry::get(g.day).unwrap();
+                let answers = (day.solve)(&input, g.parts, &[]);
+                prop_assert!(answers.is_ok(), "day {}: {:?}\n{input}", g.day, answers.err());
+            }
+        }
+    }
+}
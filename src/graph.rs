@@ -0,0 +1,171 @@
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+/// Undirected graph with named nodes, stored as sorted adjacency lists.
+///
+/// Nodes are numbered in order of first appearance.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    names: Vec<N>,
+    index: FxHashMap<N, usize>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl<N: Hash + Eq + Clone> Graph<N> {
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        let mut g = Graph {
+            names: Vec::new(),
+            index: FxHashMap::default(),
+            adjacency: Vec::new(),
+        };
+        for (a, b) in edges {
+            let a = g.insert(a);
+            let b = g.insert(b);
+            if a != b {
+                g.adjacency[a].push(b);
+                g.adjacency[b].push(a);
+            }
+        }
+        for n in g.adjacency.iter_mut() {
+            n.sort_unstable();
+            n.dedup();
+        }
+        g
+    }
+
+    fn insert(&mut self, name: N) -> usize {
+        *self.index.entry(name).or_insert_with_key(|name| {
+            self.names.push(name.clone());
+            self.adjacency.push(Vec::new());
+            self.names.len() - 1
+        })
+    }
+
+    pub fn index(&self, name: &N) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+}
+
+impl<N> Graph<N> {
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, node: usize) -> &N {
+        &self.names[node]
+    }
+
+    /// Neighbours of `node`, in increasing order.
+    pub fn neighbours(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.adjacency[a].binary_search(&b).is_ok()
+    }
+
+    /// Every triangle once, as increasing node triples.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        (0..self.len()).flat_map(move |a| {
+            let above = |n: usize| self.neighbours(n).iter().copied().filter(move |&m| m > n);
+            above(a).flat_map(move |b| {
+                above(b)
+                    .filter(move |&c| self.has_edge(a, c))
+                    .map(move |c| [a, b, c])
+            })
+        })
+    }
+
+    /// A largest set of pairwise connected nodes, in increasing order.
+    pub fn max_clique(&self) -> Vec<usize> {
+        let mut best = Vec::new();
+        self.bron_kerbosch(
+            &mut Vec::new(),
+            (0..self.len()).collect(),
+            Vec::new(),
+            &mut best,
+        );
+        best.sort_unstable();
+        best
+    }
+
+    /// Bron–Kerbosch with pivoting, `p` and `x` being sorted.
+    fn bron_kerbosch(
+        &self,
+        r: &mut Vec<usize>,
+        mut p: Vec<usize>,
+        mut x: Vec<usize>,
+        best: &mut Vec<usize>,
+    ) {
+        if p.is_empty() {
+            if x.is_empty() && r.len() > best.len() {
+                best.clone_from(r);
+            }
+            return;
+        }
+        if r.len() + p.len() <= best.len() {
+            return;
+        }
+        let pivot = p
+            .iter()
+            .chain(&x)
+            .copied()
+            .max_by_key(|&u| intersection(&p, self.neighbours(u)).len())
+            .unwrap();
+        let candidates: Vec<usize> = p
+            .iter()
+            .copied()
+            .filter(|&v| !self.has_edge(pivot, v))
+            .collect();
+        for v in candidates {
+            let n = self.neighbours(v);
+            r.push(v);
+            self.bron_kerbosch(r, intersection(&p, n), intersection(&x, n), best);
+            r.pop();
+            p.retain(|&u| u != v);
+            let k = x.partition_point(|&u| u < v);
+            x.insert(k, v);
+        }
+    }
+}
+
+fn intersection(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let (mut i, mut j) = (0, 0);
+    let mut res = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                res.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cliques() {
+        // A square with one diagonal, plus a pendant node.
+        let g = Graph::from_edges([(1, 2), (2, 3), (3, 4), (4, 1), (1, 3), (3, 1), (4, 5)]);
+        assert_eq!(g.len(), 5);
+        let (a, e) = (g.index(&1).unwrap(), g.index(&5).unwrap());
+        assert_eq!(g.neighbours(a).len(), 3);
+        assert!(!g.has_edge(a, e));
+        assert_eq!(g.triangles().count(), 2);
+        let clique: Vec<_> = g.max_clique().into_iter().map(|n| *g.name(n)).collect();
+        assert_eq!(clique.len(), 3);
+        assert!(clique.contains(&1) && clique.contains(&3));
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::pos::{Bounds, Pos, Vec2};
+
+/// Deltas of the 4-connected neighbours: up, down, left, right.
+pub const DIRS4: [Vec2; 4] = [Vec2::UP, Vec2::DOWN, Vec2::LEFT, Vec2::RIGHT];
+
+/// Deltas of the 8-connected neighbours, clockwise from up-left.
+pub const DIRS8: [Vec2; 8] = [
+    Vec2::new(-1, -1),
+    Vec2::new(0, -1),
+    Vec2::new(1, -1),
+    Vec2::new(1, 0),
+    Vec2::new(1, 1),
+    Vec2::new(0, 1),
+    Vec2::new(-1, 1),
+    Vec2::new(-1, 0),
+];
+
+/// Row-major 2D grid backed by a single `Vec`, indexed by [`Pos`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    w: usize,
+    h: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(w: usize, h: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; w * h],
+            w,
+            h,
+        }
+    }
+
+    pub fn from_vec(w: usize, h: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), w * h, "grid storage does not match {w}x{h}");
+        Grid { cells, w, h }
+    }
+
+    /// Builds a grid from a character map, one row per line.
+    pub fn parse(input: &str, mut f: impl FnMut(Pos, char) -> T) -> Self {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut w = 0;
+        let mut h = 0;
+        for (i, l) in input.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(l.chars().enumerate().map(|(j, c)| f(Pos::new(j, i), c)));
+            let len = cells.len() - before;
+            if i == 0 {
+                w = len;
+            }
+            assert_eq!(len, w, "row {i} has length {len}, expected {w}");
+            h += 1;
+        }
+        Grid { cells, w, h }
+    }
+
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.w, self.h)
+    }
+
+    pub fn in_bounds(&self, p: Pos) -> bool {
+        self.bounds().contains(p)
+    }
+
+    pub fn get(&self, p: Pos) -> Option<&T> {
+        self.in_bounds(p).then(|| &self.cells[p.index(self.w)])
+    }
+
+    pub fn get_mut(&mut self, p: Pos) -> Option<&mut T> {
+        if self.in_bounds(p) {
+            Some(&mut self.cells[p.index(self.w)])
+        } else {
+            None
+        }
+    }
+
+    /// Moves `p` by `d`, returning `None` when it leaves the grid.
+    pub fn step(&self, p: Pos, d: Vec2) -> Option<Pos> {
+        p.step(d, self.bounds())
+    }
+
+    pub fn neighbours4(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRS4.into_iter().filter_map(move |d| self.step(p, d))
+    }
+
+    pub fn neighbours8(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRS8.into_iter().filter_map(move |d| self.step(p, d))
+    }
+
+    /// Cells visited walking from `p` (excluded) in direction `d` until the edge.
+    pub fn ray(&self, p: Pos, d: Vec2) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        std::iter::successors(self.step(p, d), move |&p| self.step(p, d)).map(|p| (p, &self[p]))
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.cells[i * self.w..(i + 1) * self.w]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.w.max(1))
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> + '_ {
+        self.cells[j..].iter().step_by(self.w)
+    }
+
+    /// Top-left to bottom-right diagonal starting at `p`, `p` included.
+    pub fn diagonal(&self, p: Pos) -> impl Iterator<Item = &T> + '_ {
+        self.get(p)
+            .into_iter()
+            .chain(self.ray(p, Vec2::new(1, 1)).map(|(_, c)| c))
+    }
+
+    /// Top-right to bottom-left diagonal starting at `p`, `p` included.
+    pub fn anti_diagonal(&self, p: Pos) -> impl Iterator<Item = &T> + '_ {
+        self.get(p)
+            .into_iter()
+            .chain(self.ray(p, Vec2::new(-1, 1)).map(|(_, c)| c))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + use<T> {
+        let w = self.w;
+        (0..self.cells.len()).map(move |k| Pos::from_index(k, w))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pos, &mut T)> + '_ {
+        self.positions().zip(self.cells.iter_mut())
+    }
+
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, c)| pred(c)).map(|(p, _)| p)
+    }
+
+    pub fn position_of(&self, value: &T) -> Option<Pos>
+    where
+        T: PartialEq,
+    {
+        self.find(|c| c == value)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            w: self.w,
+            h: self.h,
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Pos) -> &T {
+        assert!(self.in_bounds(p), "{p} out of {}x{} grid", self.w, self.h);
+        &self.cells[p.index(self.w)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, p: Pos) -> &mut T {
+        assert!(self.in_bounds(p), "{p} out of {}x{} grid", self.w, self.h);
+        &mut self.cells[p.index(self.w)]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const INPUT: &str = "abc
+def";
+
+    #[test]
+    fn parse_and_index() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Pos::new(2, 1)], 'f');
+        assert_eq!(grid.get(Pos::new(0, 2)), None);
+        assert_eq!(grid.position_of(&'e'), Some(Pos::new(1, 1)));
+        assert_eq!(grid.to_string(), "abc\ndef\n");
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        assert_eq!(
+            grid.neighbours4(Pos::new(0, 0)).collect::<Vec<_>>(),
+            vec![Pos::new(0, 1), Pos::new(1, 0)]
+        );
+        assert_eq!(grid.neighbours8(Pos::new(1, 1)).count(), 5);
+        assert_eq!(grid.step(Pos::new(2, 0), Vec2::RIGHT), None);
+    }
+
+    #[test]
+    fn lines() {
+        let grid = Grid::parse(INPUT, |_, c| c);
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.diagonal(Pos::new(0, 0)).collect::<String>(), "ae");
+        assert_eq!(grid.anti_diagonal(Pos::new(2, 0)).collect::<String>(), "ce");
+        assert_eq!(
+            grid.ray(Pos::new(0, 1), Vec2::new(1, -1))
+                .map(|(_, c)| *c)
+                .collect::<String>(),
+            "b"
+        );
+    }
+}
@@ -1,18 +1,28 @@
-#![feature(extend_one)]
-#![feature(mixed_integer_ops_unsigned_sub)]
-#![feature(pattern)]
-#![feature(hash_set_entry)]
 #![warn(clippy::all)]
 #[macro_use]
 extern crate aoc_runner_derive;
 
+#[cfg(feature = "alloc-stats")]
+pub mod alloc;
+pub mod answer;
 pub mod day1;
 pub mod day10;
 pub mod day11;
 pub mod day12;
 pub mod day13;
 pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
 pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
 pub mod day3;
 pub mod day4;
 pub mod day5;
@@ -20,11 +30,24 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
-pub mod day15;
-pub mod day16;
-pub mod day17;
-pub mod day18;
-pub mod day19;
-pub mod day20;
-pub mod day21;
+pub mod direction;
+pub mod error;
+pub mod generate;
+pub mod graph;
+pub mod grid;
+mod par;
+pub mod parsing;
+pub mod pos;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(any(test, feature = "reference"))]
+pub mod reference;
+pub mod registry;
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod solution;
+pub mod timing;
+mod trace;
+pub mod viz;
 aoc_lib! { year = 2024 }
@@ -0,0 +1,352 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use nom::Parser;
+use nom::bytes::complete::{tag as nom_tag, take_while1};
+use nom::character::complete::{char, digit1, line_ending, not_line_ending, one_of, space0};
+use nom::combinator::{map_res, opt, recognize};
+use nom::error::{ContextError, ErrorKind, FromExternalError};
+use nom::multi::many0;
+use nom::sequence::{pair, preceded, separated_pair, terminated, tuple};
+
+use crate::grid::Grid;
+use crate::pos::Pos;
+
+/// Input with LF line endings and no trailing whitespace, neither on its lines
+/// nor at its end. Borrowed when already clean.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let clean = |l: &str| !l.ends_with(char::is_whitespace);
+    if !input.contains('\r') && clean(input) && input.lines().all(clean) {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len());
+    for l in input.lines() {
+        out.push_str(l.trim_end());
+        out.push('\n');
+    }
+    out.truncate(out.trim_end().len());
+    Cow::Owned(out)
+}
+
+/// Variants of an example with CRLF line endings, trailing newlines and
+/// trailing spaces, which every parser should accept.
+#[cfg(test)]
+pub(crate) fn variants(input: &str) -> Vec<String> {
+    let crlf = input.replace('\n', "\r\n");
+    let spaced: String = input.lines().map(|l| format!("{l}  \n")).collect();
+    vec![
+        format!("{input}\n"),
+        format!("{crlf}\r\n"),
+        crlf,
+        spaced.clone(),
+        format!("{}\r\n", spaced.replace('\n', "\r\n")),
+    ]
+}
+
+/// Failure of a combinator, located by the sub-slice of the input it stopped at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error<'a> {
+    pub input: &'a str,
+    pub expected: Option<Cow<'static, str>>,
+    pub kind: ErrorKind,
+}
+
+pub type PResult<'a, T> = nom::IResult<&'a str, T, Error<'a>>;
+
+impl<'a> Error<'a> {
+    /// What was expected, guessed from the failing combinator when not labelled.
+    pub fn expectation(&self) -> Cow<'static, str> {
+        self.expected.clone().unwrap_or(match self.kind {
+            ErrorKind::Eof => "end of input".into(),
+            ErrorKind::CrLf => "end of line".into(),
+            ErrorKind::Digit => "a number".into(),
+            ErrorKind::Space => "a space".into(),
+            _ => "valid input".into(),
+        })
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for Error<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        Error {
+            input,
+            expected: None,
+            kind,
+        }
+    }
+
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    /// Keeps the alternative which went the furthest.
+    fn or(self, other: Self) -> Self {
+        if other.input.len() < self.input.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for Error<'a> {
+    /// Labels errors raised where the context starts, or unlabelled ones.
+    fn add_context(input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        if other.expected.is_none() || other.input.as_ptr() == input.as_ptr() {
+            other.expected = Some(ctx.into());
+        }
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for Error<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _: E) -> Self {
+        Error {
+            input,
+            expected: None,
+            kind,
+        }
+    }
+}
+
+/// Error pointing at `at`, to be returned from a hand-written parser.
+pub fn fail<'a>(at: &'a str, expected: impl Into<Cow<'static, str>>) -> nom::Err<Error<'a>> {
+    nom::Err::Error(Error {
+        input: at,
+        expected: Some(expected.into()),
+        kind: ErrorKind::Verify,
+    })
+}
+
+/// Literal text, reported as expected in quotes.
+pub fn tag<'a>(t: &'static str) -> impl FnMut(&'a str) -> PResult<'a, &'a str> {
+    move |input| nom_tag(t)(input).map_err(|_: nom::Err<Error<'a>>| fail(input, format!("{t:?}")))
+}
+
+/// Decimal number with an optional sign.
+pub fn number<'a, T: FromStr>(expected: &'static str) -> impl FnMut(&'a str) -> PResult<'a, T> {
+    move |input| {
+        let sign = opt(one_of::<_, _, Error<'a>>("+-"));
+        map_res(recognize(pair(sign, digit1)), str::parse)(input).map_err(|_| fail(input, expected))
+    }
+}
+
+/// Two comma separated numbers, as in `3,-4`.
+pub fn xy<'a, T: FromStr>(expected: &'static str) -> impl FnMut(&'a str) -> PResult<'a, (T, T)> {
+    separated_pair(number(expected), char(','), number(expected))
+}
+
+/// Non-empty run of lowercase ASCII letters.
+pub fn lowercase<'a>(expected: &'static str) -> impl FnMut(&'a str) -> PResult<'a, &'a str> {
+    move |input| {
+        take_while1(|c: char| c.is_ascii_lowercase())(input)
+            .map_err(|_: nom::Err<Error<'a>>| fail(input, expected))
+    }
+}
+
+/// `value` preceded by `key`, as in `Register A: 729` or `p=0,4`.
+pub fn field<'a, T>(
+    key: &'static str,
+    value: impl Parser<&'a str, T, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, T> {
+    preceded(terminated(tag(key), space0), value)
+}
+
+/// Whether only blanks are left on the line.
+fn at_line_end(s: &str) -> bool {
+    let s = s.trim_start_matches([' ', '\t']);
+    s.is_empty() || s.starts_with(['\n', '\r'])
+}
+
+/// One or more `item`s separated by `sep`, stopping before a separator which
+/// leads to the end of a line, so that an item failing after a separator is
+/// reported where it fails instead of ending the list.
+pub fn separated<'a, T, S>(
+    mut item: impl Parser<&'a str, T, Error<'a>>,
+    mut sep: impl Parser<&'a str, S, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    move |input| {
+        let (mut input, first) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((next, _)) = sep.parse(input) {
+            if at_line_end(next) || next.len() == input.len() {
+                break;
+            }
+            let (next, it) = item.parse(next)?;
+            items.push(it);
+            input = next;
+        }
+        Ok((input, items))
+    }
+}
+
+/// Items separated by spaces on a single line.
+pub fn spaced<'a, T>(
+    item: impl Parser<&'a str, T, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    separated(item, take_while1(|c| c == ' ' || c == '\t'))
+}
+
+/// Items separated by commas, with optional spaces after them.
+pub fn commas<'a, T>(
+    item: impl Parser<&'a str, T, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    separated(item, pair(char(','), space0))
+}
+
+/// One item per line, up to a blank line or the end of the input.
+pub fn lines<'a, T>(
+    item: impl Parser<&'a str, T, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    separated(terminated(item, space0), line_ending)
+}
+
+/// Line break, after optional trailing spaces.
+pub fn eol(input: &str) -> PResult<'_, &str> {
+    recognize(pair(space0, line_ending))(input)
+}
+
+/// Line break followed by at least one blank line.
+pub fn blank_line(input: &str) -> PResult<'_, &str> {
+    recognize(tuple((
+        space0,
+        line_ending,
+        space0,
+        line_ending,
+        many0(pair(space0, line_ending)),
+    )))(input)
+}
+
+/// Items separated by blank lines.
+pub fn blocks<'a, T>(
+    item: impl Parser<&'a str, T, Error<'a>>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    separated(item, blank_line)
+}
+
+/// Characters up to the end of the line, `f` rejecting one by returning `None`.
+pub fn row<'a, T>(
+    expected: &'static str,
+    mut f: impl FnMut(char) -> Option<T>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<T>> {
+    move |input| {
+        let (_, line) = not_line_ending(input)?;
+        let line = line.trim_end_matches([' ', '\t']);
+        if line.is_empty() {
+            return Err(fail(input, expected));
+        }
+        let row = line
+            .char_indices()
+            .map(|(k, c)| f(c).ok_or_else(|| fail(&line[k..k + c.len_utf8()], expected)))
+            .collect::<Result<_, _>>()?;
+        Ok((&input[line.len()..], row))
+    }
+}
+
+/// Rectangular character map, `f` rejecting a cell by returning `None`.
+///
+/// The map ends at a blank line or at the end of the input, trailing spaces
+/// of its rows being ignored.
+pub fn char_map<'a, T>(
+    expected: &'static str,
+    mut f: impl FnMut(Pos, char) -> Option<T>,
+) -> impl FnMut(&'a str) -> PResult<'a, Grid<T>> {
+    move |input| {
+        let mut cells = Vec::new();
+        let mut w = None;
+        let mut h = 0;
+        let mut rest = input;
+        loop {
+            let (after, line) = not_line_ending(rest)?;
+            let row = line.trim_end_matches([' ', '\t']);
+            let mut len = 0;
+            for (x, (k, c)) in row.char_indices().enumerate() {
+                let at = &row[k..k + c.len_utf8()];
+                cells.push(f(Pos::new(x, h), c).ok_or_else(|| fail(at, expected))?);
+                len += 1;
+            }
+            match w {
+                None if len == 0 => return Err(fail(input, "a non-empty map")),
+                None => w = Some(len),
+                Some(w) if w != len => {
+                    let k = row.char_indices().nth(w).map_or(row.len(), |(k, _)| k);
+                    return Err(fail(&row[k..], format!("a row of {w} cells")));
+                }
+                _ => (),
+            }
+            h += 1;
+            rest = after;
+            match line_ending::<_, Error<'a>>(rest) {
+                Ok((next, _)) if !at_line_end(next) => rest = next,
+                _ => break,
+            }
+        }
+        Ok((rest, Grid::from_vec(w.unwrap(), h, cells)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::character::complete::multispace0;
+    use nom::combinator::all_consuming;
+
+    #[test]
+    fn lists() {
+        let mut numbers = lines(spaced(number::<i32>("a number")));
+        assert_eq!(
+            numbers("1 -2  3 \r\n4\n\n5"),
+            Ok(("\n\n5", vec![vec![1, -2, 3], vec![4]]))
+        );
+        let err = numbers("1 2\n3 x\n").unwrap_err();
+        assert!(matches!(err, nom::Err::Error(Error { input: "x\n", .. })));
+
+        let mut groups = all_consuming(terminated(
+            blocks(lines(commas(number::<u8>("a byte")))),
+            multispace0,
+        ));
+        assert_eq!(
+            groups("1, 2\n3\r\n\r\n4\n\n").map(|(_, g)| g),
+            Ok(vec![vec![vec![1, 2], vec![3]], vec![vec![4]]])
+        );
+        let err = groups("1\n\n300").unwrap_err();
+        assert!(
+            matches!(err, nom::Err::Error(e) if e.input == "300" && e.expectation() == "a byte")
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let mut robot = separated_pair(
+            field("p=", xy("a position")),
+            char(' '),
+            field("v=", xy("a velocity")),
+        );
+        assert_eq!(robot("p=0,4 v=3,-3"), Ok(("", ((0, 4), (3, -3)))));
+        let mut register = field("Register A:", number::<u64>("a value"));
+        assert_eq!(register("Register A: 729"), Ok(("", 729)));
+        let err = register("Register B: 729").unwrap_err();
+        assert!(matches!(err, nom::Err::Error(e) if e.expectation() == "\"Register A:\""));
+    }
+
+    #[test]
+    fn normalization() {
+        assert!(matches!(normalize("a\nb"), Cow::Borrowed("a\nb")));
+        assert_eq!(normalize("a \r\n\r\nb\t\r\n\n"), "a\n\nb");
+        assert!(variants("a\nb").iter().all(|v| normalize(v) == "a\nb"));
+    }
+
+    #[test]
+    fn maps() {
+        let mut map = char_map("# or .", |_, c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        });
+        let (rest, grid) = map("#.\r\n.# \n\nxx").unwrap();
+        assert_eq!((rest, grid.width(), grid.height()), ("\n\nxx", 2, 2));
+        assert!(grid[Pos::new(1, 1)]);
+        let err = map("#.\n.x").unwrap_err();
+        assert!(matches!(err, nom::Err::Error(Error { input: "x", .. })));
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// Cell of a grid, `x` being the column and `y` the row, growing downwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Displacement between two positions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec2 {
+    pub x: isize,
+    pub y: isize,
+}
+
+/// Positions `x < w` and `y < h`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Pos {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Pos { x, y }
+    }
+
+    /// `None` when the result has a negative coordinate.
+    pub fn checked_add(self, v: Vec2) -> Option<Pos> {
+        Some(Pos {
+            x: self.x.checked_add_signed(v.x)?,
+            y: self.y.checked_add_signed(v.y)?,
+        })
+    }
+
+    /// Moves by `v`, returning `None` when leaving `bounds`.
+    pub fn step(self, v: Vec2, bounds: Bounds) -> Option<Pos> {
+        self.checked_add(v).filter(|&p| bounds.contains(p))
+    }
+
+    pub fn manhattan(self, other: Pos) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Row-major index in a grid of width `w`.
+    pub fn index(self, w: usize) -> usize {
+        self.y * w + self.x
+    }
+
+    pub fn from_index(i: usize, w: usize) -> Self {
+        Pos { x: i % w, y: i / w }
+    }
+
+    pub fn to_vec(self) -> Vec2 {
+        Vec2::new(self.x as isize, self.y as isize)
+    }
+}
+
+impl Vec2 {
+    pub const UP: Vec2 = Vec2::new(0, -1);
+    pub const DOWN: Vec2 = Vec2::new(0, 1);
+    pub const LEFT: Vec2 = Vec2::new(-1, 0);
+    pub const RIGHT: Vec2 = Vec2::new(1, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn manhattan(self) -> usize {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    /// Quarter turn, clockwise on screen since `y` grows downwards.
+    pub fn clockwise(self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+
+    pub fn counter_clockwise(self) -> Self {
+        Vec2::new(self.y, -self.x)
+    }
+
+    /// Position reached on a torus of the given size, wrapping around edges.
+    pub fn wrap(self, bounds: Bounds) -> Pos {
+        Pos::new(
+            self.x.rem_euclid(bounds.w as isize) as usize,
+            self.y.rem_euclid(bounds.h as isize) as usize,
+        )
+    }
+
+    /// Position with the same coordinates, `None` if one is negative.
+    pub fn to_pos(self) -> Option<Pos> {
+        Pos::default().checked_add(self)
+    }
+}
+
+impl Bounds {
+    pub const fn new(w: usize, h: usize) -> Self {
+        Bounds { w, h }
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        p.x < self.w && p.y < self.h
+    }
+}
+
+impl Add<Vec2> for Pos {
+    type Output = Pos;
+
+    /// Panics when the result has a negative coordinate.
+    fn add(self, v: Vec2) -> Pos {
+        self.checked_add(v)
+            .unwrap_or_else(|| panic!("{self} + {v} is out of the grid"))
+    }
+}
+
+impl AddAssign<Vec2> for Pos {
+    fn add_assign(&mut self, v: Vec2) {
+        *self = *self + v;
+    }
+}
+
+impl Sub for Pos {
+    type Output = Vec2;
+
+    fn sub(self, other: Pos) -> Vec2 {
+        self.to_vec() - other.to_vec()
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, v: Vec2) -> Vec2 {
+        Vec2::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, v: Vec2) {
+        *self = *self + v;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, v: Vec2) -> Vec2 {
+        Vec2::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<isize> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, k: isize) -> Vec2 {
+        Vec2::new(self.x * k, self.y * k)
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let p = Pos::new(2, 3);
+        assert_eq!(p + Vec2::RIGHT * 2, Pos::new(4, 3));
+        assert_eq!(Pos::new(0, 5) - p, Vec2::new(-2, 2));
+        assert_eq!(p.checked_add(Vec2::LEFT * 3), None);
+        assert_eq!(p.step(Vec2::DOWN, Bounds::new(3, 4)), None);
+        assert_eq!(p.step(Vec2::UP, Bounds::new(3, 4)), Some(Pos::new(2, 2)));
+        assert_eq!(p.manhattan(Pos::new(0, 0)), 5);
+        assert_eq!(Pos::from_index(p.index(7), 7), p);
+        assert_eq!(p.to_string(), "2,3");
+        assert_eq!(Vec2::new(-1, 9).wrap(Bounds::new(3, 4)), Pos::new(2, 1));
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(Vec2::UP.clockwise(), Vec2::RIGHT);
+        assert_eq!(Vec2::RIGHT.clockwise(), Vec2::DOWN);
+        assert_eq!(Vec2::DOWN.counter_clockwise(), Vec2::RIGHT);
+        assert_eq!(-Vec2::LEFT, Vec2::RIGHT);
+        assert_eq!(Vec2::new(3, -4).manhattan(), 7);
+    }
+}
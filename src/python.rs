@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::answer::Answer;
+use crate::error::{self, SolveError};
+use crate::parsing::normalize;
+use crate::{day12, day17, registry};
+
+create_exception!(aoc2024, ParseError, PyValueError, "Malformed puzzle input.");
+create_exception!(
+    aoc2024,
+    ParamError,
+    PyValueError,
+    "Invalid override of a puzzle parameter."
+);
+
+/// `e` as a Python `ParseError`, with its location as attributes.
+fn parse_error(py: Python<'_>, e: error::ParseError) -> PyResult<PyErr> {
+    let err = ParseError::new_err(e.to_string());
+    let value = err.value(py);
+    value.setattr("line", e.line)?;
+    value.setattr("column", e.column)?;
+    value.setattr("expected", e.expected)?;
+    value.setattr("found", e.found)?;
+    Ok(err)
+}
+
+impl From<error::ParseError> for PyErr {
+    fn from(e: error::ParseError) -> Self {
+        Python::with_gil(|py| parse_error(py, e).unwrap_or_else(|err| err))
+    }
+}
+
+impl From<SolveError> for PyErr {
+    fn from(e: SolveError) -> Self {
+        match e {
+            SolveError::Parse(e) => e.into(),
+            SolveError::Param(e) => ParamError::new_err(e.to_string()),
+        }
+    }
+}
+
+impl<'py> IntoPyObject<'py> for Answer {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = Infallible;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(match self {
+            Answer::Int(n) => n.into_pyobject(py)?.into_any(),
+            Answer::Signed(n) => n.into_pyobject(py)?.into_any(),
+            Answer::Big(n) => n.into_pyobject(py)?.into_any(),
+            Answer::Text(s) => s.into_pyobject(py)?.into_any(),
+        })
+    }
+}
+
+/// Answer of `part` of `day`, `params` overriding the puzzle parameters.
+#[pyfunction]
+#[pyo3(signature = (day, part, input, params = None))]
+fn solve(
+    py: Python<'_>,
+    day: u8,
+    part: u8,
+    input: &str,
+    params: Option<HashMap<String, Bound<'_, PyAny>>>,
+) -> PyResult<Answer> {
+    let Some(entry) = registry::get(day) else {
+        return Err(PyValueError::new_err(format!("day {day} is not solved")));
+    };
+    if !matches!(part, 1 | 2) {
+        return Err(PyValueError::new_err(format!(
+            "day {day} has no part {part}"
+        )));
+    }
+    let params = params
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| Ok((key, value.str()?.to_string())))
+        .collect::<PyResult<Vec<_>>>()?;
+    let overrides: Vec<(&str, &str)> = params.iter().map(|(k, v)| (&**k, &**v)).collect();
+    let mut answers = py.allow_threads(|| (entry.solve)(input, &[part], &overrides))?;
+    Ok(answers.remove(0))
+}
+
+/// Solved days, in order.
+#[pyfunction]
+fn days() -> Vec<u32> {
+    // `Vec<u8>` would become `bytes`.
+    registry::DAYS.iter().map(|d| d.day.into()).collect()
+}
+
+/// Names of the parameters `day` accepts.
+#[pyfunction]
+fn params(day: u8) -> PyResult<Vec<&'static str>> {
+    registry::get(day)
+        .map(|d| d.params.to_vec())
+        .ok_or_else(|| PyValueError::new_err(format!("day {day} is not solved")))
+}
+
+/// Region of a day 12 garden.
+#[pyclass(name = "Region", frozen, get_all)]
+struct PyRegion {
+    plant: char,
+    /// `(x, y)` of each plot, sorted by row then column.
+    plots: Vec<(usize, usize)>,
+    perimeter: usize,
+    sides: usize,
+}
+
+#[pymethods]
+impl PyRegion {
+    #[getter]
+    fn area(&self) -> usize {
+        self.plots.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Region(plant={:?}, area={}, perimeter={}, sides={})",
+            self.plant,
+            self.area(),
+            self.perimeter,
+            self.sides
+        )
+    }
+}
+
+/// Day 12 garden.
+#[pyclass(frozen)]
+struct Garden {
+    input: day12::Input,
+}
+
+#[pymethods]
+impl Garden {
+    #[new]
+    fn new(input: &str) -> PyResult<Self> {
+        Ok(Garden {
+            input: day12::parse(&normalize(input))?,
+        })
+    }
+
+    /// Every region, in the order of their first plot.
+    fn regions(&self) -> Vec<PyRegion> {
+        day12::regions(&self.input)
+            .into_iter()
+            .map(|r| PyRegion {
+                plant: r.plant,
+                plots: r.plots.iter().map(|p| (p.x, p.y)).collect(),
+                perimeter: r.perimeter,
+                sides: r.sides,
+            })
+            .collect()
+    }
+
+    fn part1(&self) -> Answer {
+        day12::part1(&self.input)
+    }
+
+    fn part2(&self) -> Answer {
+        day12::part2(&self.input)
+    }
+}
+
+/// Day 17 three-bit computer.
+#[pyclass(frozen)]
+struct Computer {
+    input: day17::Input,
+}
+
+#[pymethods]
+impl Computer {
+    #[new]
+    fn new(input: &str) -> PyResult<Self> {
+        Ok(Computer {
+            input: day17::parse(&normalize(input))?,
+        })
+    }
+
+    /// Initial values of registers A, B and C.
+    #[getter]
+    fn registers(&self) -> (usize, usize, usize) {
+        self.input.registers()
+    }
+
+    #[getter]
+    fn program(&self) -> Vec<usize> {
+        self.input.program().to_vec()
+    }
+
+    /// Output of the program, register A defaulting to its initial value and B
+    /// and C starting from theirs.
+    #[pyo3(signature = (a = None))]
+    fn run(&self, a: Option<usize>) -> Vec<usize> {
+        self.input.run(a.unwrap_or(self.input.registers().0))
+    }
+
+    fn part1(&self) -> Answer {
+        day17::part1(&self.input)
+    }
+
+    fn part2(&self) -> Answer {
+        day17::part2(&self.input)
+    }
+}
+
+/// Solvers of Advent of Code 2024.
+#[pymodule]
+fn aoc2024(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(days, m)?)?;
+    m.add_function(wrap_pyfunction!(params, m)?)?;
+    m.add_class::<Garden>()?;
+    m.add_class::<PyRegion>()?;
+    m.add_class::<Computer>()?;
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add("ParamError", m.py().get_type::<ParamError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let m = PyModule::new(py, "aoc2024").unwrap();
+            aoc2024(&m).unwrap();
+            let solve = m.getattr("solve").unwrap();
+            let answer = solve.call1((1, 2, "3   4\n4   3")).unwrap();
+            assert_eq!(answer.extract::<u64>().unwrap(), 7);
+
+            let err = solve.call1((1, 1, "3   4\n4 x")).unwrap_err();
+            assert!(err.is_instance_of::<ParseError>(py));
+            let line = err.value(py).getattr("line").unwrap();
+            assert_eq!(line.extract::<usize>().unwrap(), 2);
+            assert!(solve.call1((26, 1, "")).is_err());
+        });
+    }
+}
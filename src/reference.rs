@@ -0,0 +1,106 @@
+use crate::answer::Answer;
+use crate::error::SolveError;
+use crate::parsing::normalize;
+use crate::registry::{Overrides, SolveFn, check, params};
+use crate::solution::Solution;
+
+/// Straightforward counterpart of a [`Solution`], far too slow for real
+/// inputs but simple enough to be trusted.
+pub trait Reference: Solution {
+    fn part1(input: &Self::Parsed, params: &Self::Params) -> Answer;
+    fn part2(input: &Self::Parsed, params: &Self::Params) -> Answer;
+}
+
+/// Type-erased [`Reference`], the naive twin of a registry day.
+pub struct Naive {
+    pub day: u8,
+    pub solve: SolveFn,
+    /// Overrides keeping both parts tractable on generated inputs.
+    pub small: &'static Overrides<'static>,
+}
+
+impl Naive {
+    pub const fn of<S: Reference>(small: &'static Overrides<'static>) -> Self {
+        Naive {
+            day: S::DAY,
+            solve: solve::<S>,
+            small,
+        }
+    }
+}
+
+fn solve<S: Reference>(
+    input: &str,
+    parts: &[u8],
+    overrides: &Overrides,
+) -> Result<Vec<Answer>, SolveError> {
+    let params = params::<S>(overrides)?;
+    let parsed = S::parse(&normalize(input))?;
+    check::<S>(&parsed, &params)?;
+    Ok(parts
+        .iter()
+        .map(|&part| match part {
+            1 => <S as Reference>::part1(&parsed, &params),
+            2 => <S as Reference>::part2(&parsed, &params),
+            _ => panic!("day {} has no part {part}", S::DAY),
+        })
+        .collect())
+}
+
+/// Every naive solver, sorted by day number.
+pub static REFERENCES: &[Naive] = &[
+    Naive::of::<crate::day1::Day1>(&[]),
+    Naive::of::<crate::day2::Day2>(&[]),
+    Naive::of::<crate::day3::Day3>(&[]),
+    Naive::of::<crate::day4::Day4>(&[]),
+    Naive::of::<crate::day5::Day5>(&[]),
+    Naive::of::<crate::day6::Day6>(&[]),
+    Naive::of::<crate::day7::Day7>(&[]),
+    Naive::of::<crate::day8::Day8>(&[]),
+    Naive::of::<crate::day9::Day9>(&[]),
+    Naive::of::<crate::day10::Day10>(&[]),
+    Naive::of::<crate::day11::Day11>(&[("blinks1", "8"), ("blinks2", "12")]),
+    Naive::of::<crate::day12::Day12>(&[]),
+    Naive::of::<crate::day13::Day13>(&[("offset", "1000")]),
+    Naive::of::<crate::day14::Day14>(&[]),
+    Naive::of::<crate::day15::Day15>(&[]),
+    Naive::of::<crate::day16::Day16>(&[]),
+    Naive::of::<crate::day17::Day17>(&[]),
+    Naive::of::<crate::day18::Day18>(&[]),
+    Naive::of::<crate::day19::Day19>(&[]),
+    Naive::of::<crate::day20::Day20>(&[("min_saving", "2")]),
+    Naive::of::<crate::day21::Day21>(&[("robots2", "3")]),
+    Naive::of::<crate::day22::Day22>(&[]),
+    Naive::of::<crate::day23::Day23>(&[]),
+    Naive::of::<crate::day24::Day24>(&[]),
+    Naive::of::<crate::day25::Day25>(&[]),
+];
+
+pub fn get(day: u8) -> Option<&'static Naive> {
+    REFERENCES
+        .binary_search_by_key(&day, |n| n.day)
+        .ok()
+        .map(|i| &REFERENCES[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate, registry};
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn agrees(seed: u64, size in 0usize..12) {
+            for naive in REFERENCES {
+                let g = generate::get(naive.day).unwrap();
+                let input = g.input(seed, size);
+                let fast = (registry::get(naive.day).unwrap().solve)(&input, g.parts, naive.small);
+                let slow = (naive.solve)(&input, g.parts, naive.small);
+                prop_assert_eq!(fast.unwrap(), slow.unwrap(), "day {}\n{}", naive.day, input);
+            }
+        }
+    }
+}
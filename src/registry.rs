@@ -0,0 +1,215 @@
+use crate::answer::Answer;
+use crate::error::{ParamError, SolveError};
+use crate::parsing::normalize;
+use crate::solution::{Solution, Tunable};
+use crate::timing::{self, Report};
+
+/// `key=value` overrides of a day's [`Tunable`] parameters.
+pub type Overrides<'a> = [(&'a str, &'a str)];
+
+pub type SolveFn = fn(&str, &[u8], &Overrides) -> Result<Vec<Answer>, SolveError>;
+pub type TimedFn = fn(&str, &[u8], &Overrides) -> Result<Report, SolveError>;
+
+/// Type-erased [`Solution`]: parses the normalized input once and runs the
+/// requested parts.
+pub struct Day {
+    pub day: u8,
+    pub solve: SolveFn,
+    /// Same as `solve`, measuring each phase.
+    pub timed: TimedFn,
+    /// Names of the parameters accepted in overrides.
+    pub params: &'static [&'static str],
+    /// Same as `solve`, without normalizing the input first.
+    #[cfg(test)]
+    raw: SolveFn,
+}
+
+impl Day {
+    pub const fn of<S: Solution>() -> Self {
+        Day {
+            day: S::DAY,
+            solve: solve::<S>,
+            timed: timed::<S>,
+            params: S::Params::KEYS,
+            #[cfg(test)]
+            raw: answers::<S>,
+        }
+    }
+}
+
+pub(crate) fn params<S: Solution>(overrides: &Overrides) -> Result<S::Params, ParamError> {
+    let mut params = S::Params::default();
+    for (key, value) in overrides {
+        params.set(key, value).map_err(|message| ParamError {
+            day: S::DAY,
+            message,
+        })?;
+    }
+    Ok(params)
+}
+
+/// [`Solution::check`] of `params` against the parsed input.
+pub(crate) fn check<S: Solution>(parsed: &S::Parsed, params: &S::Params) -> Result<(), ParamError> {
+    S::check(parsed, params).map_err(|message| ParamError {
+        day: S::DAY,
+        message,
+    })
+}
+
+fn solve<S: Solution>(
+    input: &str,
+    parts: &[u8],
+    overrides: &Overrides,
+) -> Result<Vec<Answer>, SolveError> {
+    answers::<S>(&normalize(input), parts, overrides)
+}
+
+fn answers<S: Solution>(
+    input: &str,
+    parts: &[u8],
+    overrides: &Overrides,
+) -> Result<Vec<Answer>, SolveError> {
+    let params = params::<S>(overrides)?;
+    let parsed = S::parse(input)?;
+    check::<S>(&parsed, &params)?;
+    Ok(parts
+        .iter()
+        .map(|&part| run::<S>(&parsed, &params, part))
+        .collect())
+}
+
+fn timed<S: Solution>(
+    input: &str,
+    parts: &[u8],
+    overrides: &Overrides,
+) -> Result<Report, SolveError> {
+    let params = params::<S>(overrides)?;
+    let (parsed, parse) = timing::measure(|| S::parse(&normalize(input)));
+    let parsed = parsed?;
+    check::<S>(&parsed, &params)?;
+    Ok(Report {
+        day: S::DAY,
+        parse,
+        parts: parts
+            .iter()
+            .map(|&part| {
+                let (answer, m) = timing::measure(|| run::<S>(&parsed, &params, part));
+                (part, answer, m)
+            })
+            .collect(),
+    })
+}
+
+fn run<S: Solution>(parsed: &S::Parsed, params: &S::Params, part: u8) -> Answer {
+    match part {
+        1 => S::part1(parsed, params),
+        2 => S::part2(parsed, params),
+        _ => panic!("day {} has no part {part}", S::DAY),
+    }
+}
+
+/// Every solved day, sorted by day number.
+pub static DAYS: &[Day] = &[
+    Day::of::<crate::day1::Day1>(),
+    Day::of::<crate::day2::Day2>(),
+    Day::of::<crate::day3::Day3>(),
+    Day::of::<crate::day4::Day4>(),
+    Day::of::<crate::day5::Day5>(),
+    Day::of::<crate::day6::Day6>(),
+    Day::of::<crate::day7::Day7>(),
+    Day::of::<crate::day8::Day8>(),
+    Day::of::<crate::day9::Day9>(),
+    Day::of::<crate::day10::Day10>(),
+    Day::of::<crate::day11::Day11>(),
+    Day::of::<crate::day12::Day12>(),
+    Day::of::<crate::day13::Day13>(),
+    Day::of::<crate::day14::Day14>(),
+    Day::of::<crate::day15::Day15>(),
+    Day::of::<crate::day16::Day16>(),
+    Day::of::<crate::day17::Day17>(),
+    Day::of::<crate::day18::Day18>(),
+    Day::of::<crate::day19::Day19>(),
+    Day::of::<crate::day20::Day20>(),
+    Day::of::<crate::day21::Day21>(),
+    Day::of::<crate::day22::Day22>(),
+    Day::of::<crate::day23::Day23>(),
+    Day::of::<crate::day24::Day24>(),
+    Day::of::<crate::day25::Day25>(),
+];
+
+pub fn get(day: u8) -> Option<&'static Day> {
+    DAYS.binary_search_by_key(&day, |d| d.day)
+        .ok()
+        .map(|i| &DAYS[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+    use crate::parsing::variants;
+
+    #[test]
+    fn lookup() {
+        assert_eq!(DAYS.len(), 25);
+        assert!(DAYS.windows(2).all(|w| w[0].day < w[1].day));
+        assert!(get(0).is_none());
+        let day1 = get(1).unwrap();
+        assert_eq!(
+            (day1.solve)("3   4\n4   3", &[1, 2], &[]).unwrap(),
+            vec![Answer::Int(0), Answer::Int(7)]
+        );
+        assert!((day1.solve)("3,4", &[1], &[]).is_err());
+        let report = (day1.timed)("3   4\n4   3", &[2], &[]).unwrap();
+        assert_eq!(report.parts.len(), 1);
+        assert_eq!(
+            (report.parts[0].0, &report.parts[0].1),
+            (2, &Answer::Int(7))
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        let day11 = get(11).unwrap();
+        assert_eq!(day11.params, ["blinks1", "blinks2"]);
+        assert_eq!(
+            (day11.solve)("125 17", &[1, 2], &[("blinks1", "6"), ("blinks2", "1")]).unwrap(),
+            vec![Answer::Int(22), Answer::Int(3)]
+        );
+        let err = (day11.solve)("125 17", &[1], &[("blinks", "6")]).unwrap_err();
+        assert!(matches!(err, SolveError::Param(ParamError { day: 11, .. })));
+        assert!((get(1).unwrap().solve)("3   4", &[1], &[("x", "1")]).is_err());
+    }
+
+    #[test]
+    fn checks() {
+        let rejects = |day, input, overrides: &Overrides| {
+            let err = (get(day).unwrap().solve)(input, &[1, 2], overrides).unwrap_err();
+            assert!(matches!(err, SolveError::Param(_)), "{err}");
+        };
+        rejects(21, "029A", &[("robots1", "0")]);
+        rejects(14, "p=0,4 v=3,-3", &[("width", "0")]);
+        let bytes = "5,4\n4,2\n0,1";
+        rejects(18, bytes, &[("size", "0")]);
+        rejects(18, bytes, &[("size", "5"), ("bytes", "2")]);
+        rejects(18, bytes, &[("size", "7"), ("bytes", "4")]);
+        let day18 = get(18).unwrap();
+        assert_eq!(
+            (day18.solve)(bytes, &[1], &[("size", "7"), ("bytes", "3")]).unwrap(),
+            vec![Answer::Int(12)]
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        for day in DAYS {
+            let g = generate::get(day.day).unwrap();
+            let input = g.input(2024, 6);
+            let answers = (day.solve)(&input, g.parts, &[]).unwrap();
+            for variant in variants(&input) {
+                let raw = (day.raw)(&variant, g.parts, &[]);
+                assert_eq!(raw.unwrap(), answers, "day {}: {variant:?}", day.day);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Shortest distances from the sources of a search, along with every
+/// predecessor of each node on an optimal path.
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    dist: FxHashMap<N, usize>,
+    preds: FxHashMap<N, Vec<N>>,
+}
+
+impl<N: Hash + Eq + Clone> Paths<N> {
+    fn new() -> Self {
+        Paths {
+            dist: FxHashMap::default(),
+            preds: FxHashMap::default(),
+        }
+    }
+
+    /// Records that `to` can be reached from `from` at `cost`, returning
+    /// whether it is a strict improvement.
+    fn relax(&mut self, from: Option<&N>, to: &N, cost: usize) -> bool {
+        match self.dist.get(to) {
+            Some(&d) if d < cost => false,
+            Some(&d) if d == cost => {
+                if let Some(from) = from {
+                    self.preds.entry(to.clone()).or_default().push(from.clone());
+                }
+                false
+            }
+            _ => {
+                self.dist.insert(to.clone(), cost);
+                self.preds
+                    .insert(to.clone(), from.into_iter().cloned().collect());
+                true
+            }
+        }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<usize> {
+        self.dist.get(node).copied()
+    }
+
+    /// Every reached node with its distance, in no particular order.
+    pub fn distances(&self) -> impl Iterator<Item = (&N, usize)> + Clone + '_ {
+        self.dist.iter().map(|(n, &d)| (n, d))
+    }
+
+    /// Predecessors of `node` on its optimal paths, empty for sources.
+    pub fn predecessors(&self, node: &N) -> &[N] {
+        self.preds.get(node).map_or(&[], |p| p.as_slice())
+    }
+
+    /// One optimal path from a source to `target`, both included.
+    pub fn path(&self, target: &N) -> Option<Vec<N>> {
+        self.dist.get(target)?;
+        let mut path = vec![target.clone()];
+        while let Some(p) = self.predecessors(path.last().unwrap()).first() {
+            path.push(p.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Nodes lying on at least one optimal path to one of `targets`.
+    pub fn on_paths(&self, targets: impl IntoIterator<Item = N>) -> FxHashSet<N> {
+        let mut seen = FxHashSet::default();
+        let mut stack: Vec<N> = targets
+            .into_iter()
+            .filter(|t| self.dist.contains_key(t))
+            .collect();
+        while let Some(n) = stack.pop() {
+            if seen.insert(n.clone()) {
+                stack.extend(self.predecessors(&n).iter().cloned());
+            }
+        }
+        seen
+    }
+
+    /// Number of distinct optimal paths from the sources to `target`.
+    pub fn count_paths(&self, target: &N) -> usize {
+        fn count<N: Hash + Eq + Clone>(
+            paths: &Paths<N>,
+            n: &N,
+            memo: &mut FxHashMap<N, usize>,
+        ) -> usize {
+            if let Some(&c) = memo.get(n) {
+                return c;
+            }
+            let preds = paths.predecessors(n);
+            let c = if preds.is_empty() {
+                1
+            } else {
+                preds.iter().map(|p| count(paths, p, memo)).sum()
+            };
+            memo.insert(n.clone(), c);
+            c
+        }
+        if !self.dist.contains_key(target) {
+            return 0;
+        }
+        count(self, target, &mut FxHashMap::default())
+    }
+}
+
+/// Breadth-first search where every edge costs 1.
+pub fn bfs<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Paths<N>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new();
+    let mut queue = VecDeque::new();
+    for s in sources {
+        if paths.relax(None, &s, 0) {
+            queue.push_back((0, s));
+        }
+    }
+    while let Some((d, n)) = queue.pop_front() {
+        for m in neighbours(&n) {
+            if paths.relax(Some(&n), &m, d + 1) {
+                queue.push_back((d + 1, m));
+            }
+        }
+    }
+    paths
+}
+
+/// Dijkstra's algorithm over `(neighbour, cost)` edges.
+pub fn dijkstra<N, I>(
+    sources: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Paths<N>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut paths = Paths::new();
+    // The heap holds indices into `nodes` so that `N` needs no ordering.
+    let mut nodes = Vec::new();
+    let mut heap = BinaryHeap::new();
+    for s in sources {
+        if paths.relax(None, &s, 0) {
+            heap.push(Reverse((0, nodes.len())));
+            nodes.push(s);
+        }
+    }
+    while let Some(Reverse((d, i))) = heap.pop() {
+        let n = nodes[i].clone();
+        if paths.distance(&n) != Some(d) {
+            continue;
+        }
+        for (m, cost) in neighbours(&n) {
+            if paths.relax(Some(&n), &m, d + cost) {
+                heap.push(Reverse((d + cost, nodes.len())));
+                nodes.push(m);
+            }
+        }
+    }
+    paths
+}
+
+/// A* search from `start` to the first node satisfying `goal`, returning its
+/// distance and a path to it. `heuristic` must never overestimate.
+pub fn astar<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<(usize, Vec<N>)>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut paths = Paths::new();
+    paths.relax(None, &start, 0);
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((heuristic(&start), 0, 0)));
+    let mut nodes = vec![start];
+    while let Some(Reverse((_, d, i))) = heap.pop() {
+        let n = nodes[i].clone();
+        if paths.distance(&n) != Some(d) {
+            continue;
+        }
+        if goal(&n) {
+            return Some((d, paths.path(&n).unwrap()));
+        }
+        for (m, cost) in neighbours(&n) {
+            if paths.relax(Some(&n), &m, d + cost) {
+                heap.push(Reverse((d + cost + heuristic(&m), d + cost, nodes.len())));
+                nodes.push(m);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //   0 - 1 - 3
+    //    \     /
+    //      2 -
+    fn diamond(n: &u32) -> Vec<(u32, usize)> {
+        match n {
+            0 => vec![(1, 1), (2, 1)],
+            1 => vec![(3, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn searches() {
+        let paths = bfs([0], |n| diamond(n).into_iter().map(|(m, _)| m));
+        assert_eq!(paths.distance(&3), Some(2));
+        assert_eq!(paths.count_paths(&3), 2);
+        assert_eq!(paths.on_paths([3]).len(), 4);
+        assert_eq!(paths.path(&3).unwrap().len(), 3);
+
+        let weighted = |n: &u32| diamond(n).into_iter().map(|(m, c)| (m, c * (m as usize)));
+        let paths = dijkstra([0], weighted);
+        assert_eq!(paths.distance(&3), Some(4));
+        assert_eq!(paths.path(&3), Some(vec![0, 1, 3]));
+        assert_eq!(paths.count_paths(&3), 1);
+        assert_eq!(paths.predecessors(&3), &[1]);
+
+        assert_eq!(
+            astar(0, weighted, |_| 0, |&n| n == 3),
+            Some((4, vec![0, 1, 3]))
+        );
+        assert_eq!(astar(0, weighted, |_| 0, |&n| n == 4), None);
+    }
+}
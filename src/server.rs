@@ -0,0 +1,201 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::error::SolveError;
+use crate::registry;
+
+/// Status code and JSON body of a response.
+pub type Reply = (u16, Value);
+
+fn error(status: u16, message: String) -> Reply {
+    (status, json!({ "error": { "message": message } }))
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1e3
+}
+
+/// Answers `POST /solve/{day}/{part}` with the puzzle input as `body`.
+///
+/// Parameters can be overridden in the query string, e.g.
+/// `/solve/11/1?blinks1=6`.
+pub fn route(method: &Method, url: &str, body: &[u8]) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let ["solve", day, part] = segments[..] else {
+        return error(404, format!("no route for {path}"));
+    };
+    let Some(entry) = day.parse().ok().and_then(registry::get) else {
+        return error(404, format!("day {day} is not solved"));
+    };
+    let part = match part {
+        "1" => 1,
+        "2" => 2,
+        _ => return error(404, format!("day {day} has no part {part}")),
+    };
+    if *method != Method::Post {
+        return error(405, format!("expected POST, found {method}"));
+    }
+    let Ok(input) = std::str::from_utf8(body) else {
+        return error(400, "input is not UTF-8".to_owned());
+    };
+    let overrides: Result<Vec<(&str, &str)>, String> = query
+        .split('&')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.split_once('=')
+                .ok_or_else(|| format!("expected KEY=VALUE, found {s:?}"))
+        })
+        .collect();
+    let overrides = match overrides {
+        Ok(overrides) => overrides,
+        Err(e) => return error(400, e),
+    };
+
+    match (entry.timed)(input, &[part], &overrides) {
+        Ok(report) => {
+            let (_, answer, solve) = &report.parts[0];
+            (
+                200,
+                json!({
+                    "answer": answer.to_string(),
+                    "parse_ms": millis(report.parse.elapsed),
+                    "solve_ms": millis(solve.elapsed),
+                }),
+            )
+        }
+        Err(SolveError::Parse(e)) => (
+            422,
+            json!({
+                "error": {
+                    "line": e.line,
+                    "column": e.column,
+                    "expected": e.expected,
+                    "found": e.found,
+                    "message": e.to_string(),
+                },
+            }),
+        ),
+        Err(e @ SolveError::Param(_)) => error(400, e.to_string()),
+    }
+}
+
+/// [`route`], answering 500 if the solver panics.
+pub fn handle(method: &Method, url: &str, body: &[u8]) -> Reply {
+    unpanic(|| route(method, url, body))
+}
+
+/// `reply`, or a 500 with the panic message if it panics.
+fn unpanic(reply: impl FnOnce() -> Reply) -> Reply {
+    panic::catch_unwind(AssertUnwindSafe(reply)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "the solver panicked".to_owned());
+        error(500, message)
+    })
+}
+
+/// Answers requests one at a time until the server is unblocked.
+pub fn serve(server: &Server) {
+    let json = Header::from_bytes("Content-Type", "application/json").unwrap();
+    for mut request in server.incoming_requests() {
+        let mut body = Vec::new();
+        let (status, value) = match request.as_reader().read_to_end(&mut body) {
+            Ok(_) => handle(request.method(), request.url(), &body),
+            Err(e) => error(400, format!("unreadable body: {e}")),
+        };
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(json.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("{e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn routes() {
+        let (status, body) = route(&Method::Post, "/solve/1/2", b"3   4\n4   3");
+        assert_eq!(status, 200);
+        assert_eq!(body["answer"], "7");
+        assert!(body["parse_ms"].is_f64() && body["solve_ms"].is_f64());
+
+        let (status, body) = route(&Method::Post, "/solve/11/1?blinks1=6", b"125 17");
+        assert_eq!((status, &body["answer"]), (200, &json!("22")));
+
+        assert_eq!(route(&Method::Post, "/solve/26/1", b"").0, 404);
+        assert_eq!(route(&Method::Post, "/solve/1/3", b"").0, 404);
+        assert_eq!(route(&Method::Post, "/days", b"").0, 404);
+        assert_eq!(route(&Method::Get, "/solve/1/1", b"").0, 405);
+        assert_eq!(route(&Method::Post, "/solve/1/1", &[0xff]).0, 400);
+        assert_eq!(route(&Method::Post, "/solve/11/1?blinks=6", b"1").0, 400);
+        assert_eq!(route(&Method::Post, "/solve/11/1?blinks1", b"1").0, 400);
+    }
+
+    #[test]
+    fn parse_error() {
+        let (status, body) = route(&Method::Post, "/solve/1/1", b"3   4\n4 x");
+        assert_eq!(status, 422);
+        assert_eq!(body["error"]["line"], 2);
+        assert_eq!(body["error"]["column"], 3);
+    }
+
+    #[test]
+    fn panics() {
+        let maze = b"#######\n#S.#.E#\n#######";
+        let (status, body) = handle(&Method::Post, "/solve/16/1", maze);
+        assert_eq!((status, &body["answer"]), (200, &json!("0")));
+
+        let (status, body) = handle(&Method::Post, "/solve/18/2?size=7&bytes=1", b"0,1");
+        assert_eq!((status, &body["answer"]), (200, &json!("0")));
+
+        let (status, body) = unpanic(|| panic!("the solver gave up"));
+        assert_eq!(status, 500);
+        assert_eq!(body["error"]["message"], "the solver gave up");
+        let (status, body) = unpanic(|| panic!("{} is unsolvable", 18));
+        assert_eq!(status, 500);
+        assert_eq!(body["error"]["message"], "18 is unsolvable");
+    }
+
+    #[test]
+    fn localhost() {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = std::thread::spawn({
+            let server = Arc::clone(&server);
+            move || serve(&server)
+        });
+
+        let input = "3   4\n4   3\n";
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /solve/1/1 HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{input}",
+            input.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+        assert!(head.contains("application/json"), "{head}");
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["answer"], "0");
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+}
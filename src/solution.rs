@@ -0,0 +1,123 @@
+use crate::answer::Answer;
+use crate::error::ParseError;
+
+/// A day of the calendar: an input parser and the two puzzle parts.
+pub trait Solution {
+    const DAY: u8;
+
+    type Parsed;
+    /// Tunable puzzle constants, `()` for days without any.
+    type Params: Tunable;
+
+    fn parse(input: &str) -> Result<Self::Parsed, ParseError>;
+    fn part1(input: &Self::Parsed, params: &Self::Params) -> Answer;
+    fn part2(input: &Self::Parsed, params: &Self::Params) -> Answer;
+
+    /// Rejects parameters the parts cannot be solved with, e.g. overrides out
+    /// of range or not fitting `input`.
+    fn check(_input: &Self::Parsed, _params: &Self::Params) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Puzzle constants which can be overridden by name, e.g. from the command line.
+pub trait Tunable: Default {
+    /// Names of the fields that can be set.
+    const KEYS: &'static [&'static str];
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+impl Tunable for () {
+    const KEYS: &'static [&'static str] = &[];
+
+    fn set(&mut self, key: &str, _: &str) -> Result<(), String> {
+        Err(unknown_key(key, Self::KEYS))
+    }
+}
+
+pub(crate) fn unknown_key(key: &str, keys: &[&str]) -> String {
+    if keys.is_empty() {
+        format!("unknown parameter {key:?}, this day has none")
+    } else {
+        format!(
+            "unknown parameter {key:?}, expected one of {}",
+            keys.join(", ")
+        )
+    }
+}
+
+/// Declares a `Params` struct with a default for each field and implements
+/// [`Tunable`] for it.
+macro_rules! params {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            $($(#[$fattr:meta])* pub $field:ident: $ty:ty = $default:expr),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name {
+            $($(#[$fattr])* pub $field: $ty),*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    $($field: $default),*
+                }
+            }
+        }
+
+        impl $crate::solution::Tunable for $name {
+            const KEYS: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+                match key {
+                    $(stringify!($field) => {
+                        self.$field = value
+                            .parse()
+                            .map_err(|e| format!("invalid value {value:?} for {key}: {e}"))?
+                    })*
+                    _ => return Err($crate::solution::unknown_key(key, Self::KEYS)),
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use params;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    params! {
+        pub struct Params {
+            pub steps: usize = 3,
+            pub offset: i64 = -1,
+        }
+    }
+
+    #[test]
+    fn set() {
+        let mut p = Params::default();
+        assert_eq!(
+            p,
+            Params {
+                steps: 3,
+                offset: -1
+            }
+        );
+        p.set("offset", "42").unwrap();
+        assert_eq!(p.offset, 42);
+        assert!(p.set("steps", "-2").is_err());
+        assert_eq!(
+            p.set("step", "2").unwrap_err(),
+            "unknown parameter \"step\", expected one of steps, offset"
+        );
+        assert!(().set("steps", "2").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::answer::Answer;
+
+/// Allocations made while running a phase, see the `alloc-stats` feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocs {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+/// Cost of one phase of a solver.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Measure {
+    pub elapsed: Duration,
+    /// `None` unless built with the `alloc-stats` feature.
+    pub allocs: Option<Allocs>,
+}
+
+/// Runs `f` and measures its wall time and, when enabled, its allocations.
+///
+/// Allocation counters are process-wide, so other threads allocating at the
+/// same time are counted too.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Measure) {
+    let before = allocs();
+    let start = Instant::now();
+    let res = f();
+    let elapsed = start.elapsed();
+    let allocs = before.zip(allocs()).map(|(b, a)| Allocs {
+        count: a.count - b.count,
+        bytes: a.bytes - b.bytes,
+    });
+    (res, Measure { elapsed, allocs })
+}
+
+#[cfg(feature = "alloc-stats")]
+fn allocs() -> Option<Allocs> {
+    Some(crate::alloc::snapshot())
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+fn allocs() -> Option<Allocs> {
+    None
+}
+
+/// Answers of a day along with the cost of each phase.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub parse: Measure,
+    pub parts: Vec<(u8, Answer, Measure)>,
+}
+
+impl Report {
+    pub fn phases(&self) -> impl Iterator<Item = (String, Measure)> + '_ {
+        std::iter::once(("parse".to_owned(), self.parse)).chain(
+            self.parts
+                .iter()
+                .map(|(part, _, m)| (format!("part{part}"), *m)),
+        )
+    }
+}
+
+/// Reports of several days, displayed as one row per phase.
+pub struct Table<'a>(pub &'a [Report]);
+
+impl fmt::Display for Table<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let with_allocs = self
+            .0
+            .iter()
+            .any(|r| r.phases().any(|(_, m)| m.allocs.is_some()));
+        write!(f, "{:>4} {:>6} {:>12}", "day", "phase", "time")?;
+        if with_allocs {
+            write!(f, " {:>10} {:>14}", "allocs", "bytes")?;
+        }
+        writeln!(f)?;
+
+        let mut total = Duration::ZERO;
+        for report in self.0 {
+            for (phase, m) in report.phases() {
+                total += m.elapsed;
+                write!(f, "{:>4} {:>6} {:>12.2?}", report.day, phase, m.elapsed)?;
+                if let Some(a) = m.allocs {
+                    write!(f, " {:>10} {:>14}", a.count, a.bytes)?;
+                }
+                writeln!(f)?;
+            }
+        }
+        writeln!(f, "{:>11} {:>12.2?}", "total", total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let (v, m) = measure(|| vec![1u8; 64]);
+        assert_eq!(v.len(), 64);
+        if let Some(a) = m.allocs {
+            assert!(a.count >= 1 && a.bytes >= 64);
+        }
+        let report = Report {
+            day: 3,
+            parse: m,
+            parts: vec![(1, Answer::Int(1), m), (2, Answer::Int(2), m)],
+        };
+        let table = Table(&[report]).to_string();
+        let rows: Vec<_> = table.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[2].trim_start().starts_with("3  part1"));
+        assert!(rows[4].trim_start().starts_with("total"));
+    }
+}
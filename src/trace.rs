@@ -0,0 +1,103 @@
+/// `tracing::trace!` with the `tracing` feature, nothing otherwise.
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!($($arg)+)
+    };
+}
+
+/// `tracing::debug!` with the `tracing` feature, nothing otherwise.
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!($($arg)+)
+    };
+}
+
+pub(crate) use {debug, trace};
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::{Dispatch, Event, Level, Subscriber, dispatcher};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    use crate::registry;
+
+    /// Messages of the events seen, with their level.
+    #[derive(Clone, Default)]
+    struct Messages(Arc<Mutex<Vec<(Level, String)>>>);
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Messages {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut message = Message(String::new());
+            event.record(&mut message);
+            let level = *event.metadata().level();
+            self.0.lock().unwrap().push((level, message.0));
+        }
+    }
+
+    fn events(day: u8, input: &str) -> Vec<(Level, String)> {
+        let messages = Messages::default();
+        let dispatch = Dispatch::new(tracing_subscriber::registry().with(messages.clone()));
+        let solve = || (registry::get(day).unwrap().solve)(input, &[1, 2], &[]).unwrap();
+        // Rayon workers do not inherit the scoped subscriber, so run on a pool
+        // whose threads set it themselves.
+        #[cfg(feature = "parallel")]
+        let solve = {
+            let dispatch = dispatch.clone();
+            let pool = rayon::ThreadPoolBuilder::new()
+                .start_handler(move |_| std::mem::forget(dispatcher::set_default(&dispatch)))
+                .build()
+                .unwrap();
+            move || pool.install(solve)
+        };
+        dispatcher::with_default(&dispatch, solve);
+        messages.0.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn guard() {
+        let events = events(
+            6,
+            "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...",
+        );
+        assert!(events.contains(&(Level::TRACE, "guard step".to_owned())));
+        let loops = events.iter().filter(|(_, m)| m == "loop detected");
+        assert!(loops.clone().all(|(l, _)| *l == Level::DEBUG));
+        assert_eq!(loops.count(), 6);
+    }
+
+    #[test]
+    fn program() {
+        let events = events(
+            17,
+            "Register A: 10\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0",
+        );
+        let executed = events.iter().filter(|(_, m)| m == "execute").count();
+        assert!(executed > 0);
+        assert!(events.iter().all(|(l, _)| *l == Level::TRACE));
+    }
+}
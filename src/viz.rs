@@ -0,0 +1,400 @@
+use std::fmt::{self, Write as _};
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::SolveError;
+use crate::grid::Grid;
+use crate::parsing::normalize;
+use crate::pos::Pos;
+use crate::registry::{Overrides, check, params};
+use crate::solution::Solution;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+    pub const DARK: Rgb = Rgb(48, 48, 48);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(80, 200, 60);
+    pub const BLUE: Rgb = Rgb(38, 139, 210);
+    pub const YELLOW: Rgb = Rgb(230, 190, 0);
+
+    fn luma(self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+}
+
+/// How a cell is drawn: a character in text frames, a colour in images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub color: Rgb,
+}
+
+impl Glyph {
+    pub const fn new(ch: char, color: Rgb) -> Self {
+        Glyph { ch, color }
+    }
+}
+
+/// One state of a simulation, ready to be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    glyphs: Grid<Glyph>,
+}
+
+impl Frame {
+    /// Draws each cell of `grid` with `f`.
+    pub fn new<T>(grid: &Grid<T>, f: impl FnMut(&T) -> Glyph) -> Self {
+        Frame {
+            glyphs: grid.map(f),
+        }
+    }
+
+    pub fn glyphs(&self) -> &Grid<Glyph> {
+        &self.glyphs
+    }
+
+    /// Draws `glyph` over `cells`, ignoring the ones off the frame.
+    pub fn path(mut self, cells: impl IntoIterator<Item = Pos>, glyph: Glyph) -> Self {
+        for p in cells {
+            if let Some(g) = self.glyphs.get_mut(p) {
+                *g = glyph;
+            }
+        }
+        self
+    }
+
+    /// Recolours `cells`, keeping their characters.
+    pub fn highlight(mut self, cells: impl IntoIterator<Item = Pos>, color: Rgb) -> Self {
+        for p in cells {
+            if let Some(g) = self.glyphs.get_mut(p) {
+                g.color = color;
+            }
+        }
+        self
+    }
+
+    /// Text coloured with 24-bit ANSI escapes.
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.glyphs.rows() {
+            let mut current = None;
+            for glyph in row {
+                if current != Some(glyph.color) {
+                    let Rgb(r, g, b) = glyph.color;
+                    write!(out, "\x1b[38;2;{r};{g};{b}m").unwrap();
+                    current = Some(glyph.color);
+                }
+                out.push(glyph.ch);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Binary PPM image, each cell being a `scale` pixels square.
+    pub fn ppm(&self, scale: usize) -> Vec<u8> {
+        self.image("P6", scale, |c| vec![c.0, c.1, c.2])
+    }
+
+    /// Binary PGM image of the luminance of `ppm`.
+    pub fn pgm(&self, scale: usize) -> Vec<u8> {
+        self.image("P5", scale, |c| vec![c.luma()])
+    }
+
+    fn image(&self, magic: &str, scale: usize, pixel: impl Fn(Rgb) -> Vec<u8>) -> Vec<u8> {
+        let (w, h) = (self.glyphs.width() * scale, self.glyphs.height() * scale);
+        let mut out = format!("{magic}\n{w} {h}\n255\n").into_bytes();
+        for row in self.glyphs.rows() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|g| pixel(g.color).repeat(scale))
+                .collect();
+            for _ in 0..scale {
+                out.extend_from_slice(&line);
+            }
+        }
+        out
+    }
+}
+
+/// Plain text snapshot, colours dropped.
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.glyphs.map(|g| g.ch).fmt(f)
+    }
+}
+
+/// File format of recorded frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Ansi,
+    Ppm,
+    Pgm,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Ansi => "ans",
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+        }
+    }
+
+    pub fn render(self, frame: &Frame, scale: usize) -> Vec<u8> {
+        match self {
+            Format::Text => frame.to_string().into_bytes(),
+            Format::Ansi => frame.ansi().into_bytes(),
+            Format::Ppm => frame.ppm(scale),
+            Format::Pgm => frame.pgm(scale),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "ansi" => Ok(Format::Ansi),
+            "ppm" => Ok(Format::Ppm),
+            "pgm" => Ok(Format::Pgm),
+            _ => Err(format!(
+                "unknown format {s:?}, expected one of text, ansi, ppm, pgm"
+            )),
+        }
+    }
+}
+
+/// Writes frames as numbered files of a directory.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+    every: usize,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>, format: Format) -> Self {
+        Recorder {
+            dir: dir.into(),
+            format,
+            scale: 4,
+            every: 1,
+        }
+    }
+
+    /// Side in pixels of a cell in images.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Keeps one frame out of `every`, the last one always being kept.
+    pub fn every(mut self, every: usize) -> Self {
+        self.every = every.max(1);
+        self
+    }
+
+    /// Writes the kept `frames`, returning how many there were.
+    pub fn record(&self, frames: impl Iterator<Item = Frame>) -> io::Result<usize> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut written = 0;
+        let mut skipped = None;
+        for (i, frame) in frames.enumerate() {
+            if i % self.every == 0 {
+                self.write(written, &frame)?;
+                written += 1;
+                skipped = None;
+            } else {
+                skipped = Some(frame);
+            }
+        }
+        if let Some(frame) = skipped {
+            self.write(written, &frame)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    fn write(&self, i: usize, frame: &Frame) -> io::Result<()> {
+        let path = self.dir.join(format!("{i:05}.{}", self.format.extension()));
+        std::fs::write(path, self.format.render(frame, self.scale))
+    }
+}
+
+/// Simulation whose intermediate states can be rendered.
+pub trait Animate: Solution {
+    /// States the simulation of `part` goes through, in order.
+    fn frames<'a>(
+        input: &'a Self::Parsed,
+        params: &'a Self::Params,
+        part: u8,
+    ) -> Box<dyn Iterator<Item = Frame> + 'a>;
+}
+
+/// Anything preventing a day from being animated.
+#[derive(Debug)]
+pub enum VizError {
+    Solve(SolveError),
+    Io(io::Error),
+}
+
+impl fmt::Display for VizError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VizError::Solve(e) => e.fmt(f),
+            VizError::Io(e) => write!(f, "cannot write frames: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VizError {}
+
+impl From<SolveError> for VizError {
+    fn from(e: SolveError) -> Self {
+        VizError::Solve(e)
+    }
+}
+
+impl From<io::Error> for VizError {
+    fn from(e: io::Error) -> Self {
+        VizError::Io(e)
+    }
+}
+
+pub type RecordFn = fn(&str, u8, &Overrides, &Recorder) -> Result<usize, VizError>;
+
+/// Type-erased [`Animate`]: parses the normalized input and records the
+/// frames of one part.
+pub struct Animation {
+    pub day: u8,
+    pub record: RecordFn,
+}
+
+impl Animation {
+    pub const fn of<S: Animate>() -> Self {
+        Animation {
+            day: S::DAY,
+            record: record::<S>,
+        }
+    }
+}
+
+fn record<S: Animate>(
+    input: &str,
+    part: u8,
+    overrides: &Overrides,
+    recorder: &Recorder,
+) -> Result<usize, VizError> {
+    let params = params::<S>(overrides).map_err(SolveError::from)?;
+    let parsed = S::parse(&normalize(input)).map_err(SolveError::from)?;
+    check::<S>(&parsed, &params).map_err(SolveError::from)?;
+    if !matches!(part, 1 | 2) {
+        panic!("day {} has no part {part}", S::DAY);
+    }
+    Ok(recorder.record(S::frames(&parsed, &params, part))?)
+}
+
+/// Every animated day, sorted by day number.
+pub static ANIMATIONS: &[Animation] = &[
+    Animation::of::<crate::day6::Day6>(),
+    Animation::of::<crate::day14::Day14>(),
+    Animation::of::<crate::day15::Day15>(),
+    Animation::of::<crate::day16::Day16>(),
+    Animation::of::<crate::day18::Day18>(),
+];
+
+pub fn get(day: u8) -> Option<&'static Animation> {
+    ANIMATIONS
+        .binary_search_by_key(&day, |a| a.day)
+        .ok()
+        .map(|i| &ANIMATIONS[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    fn frame() -> Frame {
+        let grid = Grid::parse("#.\n..", |_, c| c == '#');
+        Frame::new(&grid, |&wall| {
+            if wall {
+                Glyph::new('#', Rgb::WHITE)
+            } else {
+                Glyph::new('.', Rgb::BLACK)
+            }
+        })
+        .path([Pos::new(1, 1), Pos::new(2, 1)], Glyph::new('O', Rgb::RED))
+        .highlight([Pos::new(1, 0)], Rgb::BLUE)
+    }
+
+    #[test]
+    fn render() {
+        let frame = frame();
+        assert_eq!(frame.to_string(), "#.\n.O\n");
+        assert_eq!(
+            frame.ansi().lines().next().unwrap(),
+            "\x1b[38;2;255;255;255m#\x1b[38;2;38;139;210m.\x1b[0m"
+        );
+        let ppm = frame.ppm(2);
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+        assert_eq!(&ppm[11..17], &[255; 6]);
+        let pgm = frame.pgm(1);
+        assert_eq!(pgm, b"P5\n2 2\n255\n\xff\x74\x00\x64");
+        assert_eq!("pgm".parse(), Ok(Format::Pgm));
+        assert!("png".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn record() {
+        let dir = std::env::temp_dir().join(format!("aoc2024-viz-{}", std::process::id()));
+        let recorder = Recorder::new(&dir, Format::Text).every(3);
+        assert_eq!(recorder.record(std::iter::repeat_n(frame(), 5)).unwrap(), 3);
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["00000.txt", "00001.txt", "00002.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("00002.txt")).unwrap(),
+            "#.\n.O\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn animations() {
+        assert!(ANIMATIONS.windows(2).all(|w| w[0].day < w[1].day));
+        assert!(get(1).is_none());
+        let dir = std::env::temp_dir().join(format!("aoc2024-anim-{}", std::process::id()));
+        for animation in ANIMATIONS {
+            let input = generate::get(animation.day).unwrap().input(7, 5);
+            for part in [1, 2] {
+                let recorder =
+                    Recorder::new(dir.join(format!("{}-{part}", animation.day)), Format::Ppm)
+                        .every(usize::MAX);
+                let frames = (animation.record)(&input, part, &[], &recorder).unwrap();
+                assert!(
+                    (1..=2).contains(&frames),
+                    "day {} part {part}",
+                    animation.day
+                );
+            }
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+//! Runs every day on its real input and compares with `answers/2024.toml`.
+
+mod common;
+
+use aoc2024::registry;
+use common::days;
+
+fn check(day: u8) {
+    let Some(input) = common::input(day) else {
+        return;
+    };
+    let entry = registry::get(day).unwrap_or_else(|| panic!("day {day} is not registered"));
+    let answers = (entry.solve)(&input, &[1, 2], &[]).unwrap();
+    for ((part, answer), expected) in (1..).zip(answers).zip(common::expected(day)) {
+        if let Some(expected) = expected {
+            assert_eq!(answer.to_string(), expected, "day {day} part {part}");
+        }
+    }
+}
+
+days!(
+    day1 => 1,
+    day2 => 2,
+    day3 => 3,
+    day4 => 4,
+    day5 => 5,
+    day6 => 6,
+    day7 => 7,
+    day8 => 8,
+    day9 => 9,
+    day10 => 10,
+    day11 => 11,
+    day12 => 12,
+    day13 => 13,
+    day14 => 14,
+    day15 => 15,
+    day16 => 16,
+    day17 => 17,
+    day18 => 18,
+    day19 => 19,
+    day20 => 20,
+    day21 => 21,
+    day22 => 22,
+    day23 => 23,
+    day24 => 24,
+    day25 => 25,
+);
@@ -0,0 +1,49 @@
+//! Real inputs and their recorded answers in `answers/2024.toml`.
+//!
+//! Inputs are personal and may be missing from a checkout, in which case the
+//! day is skipped.
+
+use std::path::Path;
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Input of `day`, `None` if it is missing.
+pub fn input(day: u8) -> Option<String> {
+    let path = root().join(format!("input/2024/day{day}.txt"));
+    let input = std::fs::read_to_string(&path);
+    if input.is_err() {
+        eprintln!("skipping day {day}: no {}", path.display());
+    }
+    input.ok()
+}
+
+/// Recorded answers of both parts of `day`.
+pub fn expected(day: u8) -> [Option<String>; 2] {
+    let manifest = std::fs::read_to_string(root().join("answers/2024.toml")).unwrap();
+    let manifest: toml::Table = manifest.parse().unwrap();
+    let Some(answers) = manifest.get(&format!("day{day}")) else {
+        eprintln!("skipping day {day}: no recorded answers");
+        return [None, None];
+    };
+    [1, 2].map(|part| {
+        let answer = answers.get(format!("part{part}"))?;
+        let answer = answer.as_str().expect("answers are recorded as strings");
+        Some(answer.to_owned())
+    })
+}
+
+/// One test per day, named after it, calling `check` with its number.
+macro_rules! days {
+    ($($name:ident => $day:literal),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                check($day);
+            }
+        )*
+    };
+}
+
+pub(crate) use days;
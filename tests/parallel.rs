@@ -0,0 +1,39 @@
+//! Runs the days with parallel loops on their real inputs, once on a single
+//! thread and once on several, and compares both with the serial answers of
+//! `answers/2024.toml`.
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use aoc2024::registry;
+use common::days;
+
+fn solve_on(threads: usize, day: u8, input: &str) -> Vec<String> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let answers = pool.install(|| (registry::get(day).unwrap().solve)(input, &[1, 2], &[]));
+    answers.unwrap().iter().map(|a| a.to_string()).collect()
+}
+
+fn check(day: u8) {
+    let Some(input) = common::input(day) else {
+        return;
+    };
+    let serial = solve_on(1, day, &input);
+    assert_eq!(solve_on(4, day, &input), serial, "day {day}");
+    for ((part, answer), expected) in (1..).zip(serial).zip(common::expected(day)) {
+        if let Some(expected) = expected {
+            assert_eq!(answer, expected, "day {day} part {part}");
+        }
+    }
+}
+
+days!(
+    day6 => 6,
+    day7 => 7,
+    day10 => 10,
+    day19 => 19,
+    day20 => 20,
+);